@@ -0,0 +1,94 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use ginkgo::{GObj, Object, VM};
+
+
+const LIST_LEN: usize = 10_000;
+const LONG_LIST_LEN: usize = 100_000;
+const NEST_DEPTH: usize = 200;
+
+
+fn int_list(vm: &mut VM, len: usize) -> Object {
+    let items: Vec<_> = (0..len as isize).map(|i| vm.int(i)).collect();
+    vm.list(&items)
+}
+
+fn cons(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cons");
+    group.throughput(Throughput::Elements(LONG_LIST_LEN as u64));
+    let vm = VM::new();
+    let items: Vec<_> = (0..LONG_LIST_LEN as isize).map(|i| vm.int(i)).collect();
+
+    group.bench_function("list", |b| b.iter_batched_ref(
+        VM::new,
+        |vm| vm.list(&items),
+        BatchSize::SmallInput,
+    ));
+    group.bench_function("repeated", |b| b.iter_batched_ref(
+        VM::new,
+        |vm| items.iter().rev().fold(Object::Nil, |list, item| vm.cons(*item, list)),
+        BatchSize::SmallInput,
+    ));
+    group.finish();
+}
+
+fn traversal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("traversal");
+    group.throughput(Throughput::Elements(LIST_LEN as u64));
+    let mut vm = VM::new();
+    let list = int_list(&mut vm, LIST_LEN).root(&mut vm);
+
+    group.bench_function("length", |b| b.iter(|| vm.list_parts(list.unroot()).0.len()));
+    group.bench_function("reverse", |b| b.iter(|| {
+        let mut reversed = Object::Nil;
+        let mut tail = list.unroot();
+        while let (Some(car), Some(cdr)) = (vm.car(tail), vm.cdr(tail)) {
+            reversed = vm.cons(car, reversed);
+            tail = cdr;
+        }
+        reversed
+    }));
+    group.finish();
+}
+
+fn gc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gc");
+    group.throughput(Throughput::Elements(LIST_LEN as u64));
+
+    group.bench_function("live", |b| b.iter_batched_ref(
+        || {
+            let mut vm = VM::new();
+            let list = int_list(&mut vm, LIST_LEN).root(&mut vm);
+            (vm, list)
+        },
+        |(vm, _)| vm.gc(),
+        BatchSize::SmallInput,
+    ));
+    group.bench_function("garbage", |b| b.iter_batched_ref(
+        || {
+            let mut vm = VM::new();
+            int_list(&mut vm, LIST_LEN);
+            vm
+        },
+        |vm| vm.gc(),
+        BatchSize::SmallInput,
+    ));
+    group.finish();
+}
+
+fn display(c: &mut Criterion) {
+    let mut vm = VM::new();
+    let mut tree = vm.string(String::from("leaf"));
+    for i in 0..NEST_DEPTH as isize {
+        let row = int_list(&mut vm, 10);
+        let n = vm.int(i);
+        tree = vm.list(&[n, tree, row]);
+    }
+    let tree = tree.root(&mut vm);
+
+    c.bench_function("display", |b| b.iter(|| format!("{}", vm.wrap(tree.unroot()))));
+}
+
+
+criterion_group!(benches, cons, traversal, gc, display);
+criterion_main!(benches);
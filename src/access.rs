@@ -0,0 +1,46 @@
+use crate::{DObj, Foreign, GObj, HVal, NativeFn, Object, VM};
+
+
+/// Read-only view of a heap value, returned by `VM::get_ref`.  Unlike
+/// `HVal`, this does not expose how values are stored.
+#[derive(Clone, Copy, Debug)]
+pub enum Ref<'a> {
+    Cons(Object, Object),
+    Vec(&'a [Object]),
+    String(&'a str),
+    Rational(isize, isize),
+    #[cfg(feature = "bignum")]
+    BigInt(&'a num_bigint::BigInt),
+    Native(NativeFn),
+    Foreign(&'a Foreign),
+    Env,
+    Closure { params: Object, body: Object, env: Object },
+    Error { message: &'a str, irritants: &'a [Object] },
+    Values(&'a [Object]),
+    Promise { thunk: Object, value: Option<Object> },
+}
+
+
+impl VM {
+    /// Get a read-only view of the heap value of an object, for
+    /// branching on its type.  Returns `None` for stack values and dead
+    /// objects.
+    pub fn get_ref(&self, obj: impl GObj) -> Option<Ref<'_>> {
+        Some(match self.direct(obj) {
+            DObj::H(&HVal::Cons(car, cdr)) => Ref::Cons(car, cdr),
+            DObj::H(HVal::Vec(items)) => Ref::Vec(items),
+            DObj::H(HVal::String(s)) => Ref::String(s),
+            DObj::H(&HVal::Rational(n, d)) => Ref::Rational(n, d),
+            #[cfg(feature = "bignum")]
+            DObj::H(HVal::BigInt(v)) => Ref::BigInt(v),
+            DObj::H(&HVal::Native(f)) => Ref::Native(f),
+            DObj::H(HVal::Foreign(v)) => Ref::Foreign(v),
+            DObj::H(HVal::Env { .. }) => Ref::Env,
+            DObj::H(&HVal::Closure { params, body, env }) => Ref::Closure { params, body, env },
+            DObj::H(HVal::Error { message, irritants }) => Ref::Error { message, irritants },
+            DObj::H(HVal::Values(values)) => Ref::Values(values),
+            DObj::H(&HVal::Promise { thunk, value }) => Ref::Promise { thunk, value },
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use crate::VM;
+
+
+/// Default number of heap objects that triggers an automatic
+/// garbage collection.
+pub(crate) const DEFAULT_GC_THRESHOLD: usize = 10_000;
+
+/// Default limit on how deeply the reader nests lists, vectors and
+/// quotes.
+pub(crate) const DEFAULT_MAX_READ_DEPTH: usize = 256;
+
+
+/// Builder for configuring a VM before creating it.
+#[derive(Clone, Debug)]
+pub struct VmBuilder {
+    gc_threshold: usize,
+    auto_gc: bool,
+    heap_limit: Option<usize>,
+    max_read_depth: usize,
+}
+
+impl Default for VmBuilder {
+    fn default() -> VmBuilder {
+        VmBuilder {
+            gc_threshold: DEFAULT_GC_THRESHOLD,
+            auto_gc: false,
+            heap_limit: None,
+            max_read_depth: DEFAULT_MAX_READ_DEPTH,
+        }
+    }
+}
+
+impl VmBuilder {
+    /// Create a builder with the default configuration.
+    pub fn new() -> VmBuilder {
+        VmBuilder::default()
+    }
+
+    /// Set the number of heap objects at which an automatic garbage
+    /// collection is triggered.
+    pub fn gc_threshold(mut self, objects: usize) -> VmBuilder {
+        self.gc_threshold = objects;
+        self
+    }
+
+    /// Enable or disable automatic garbage collection.  When enabled,
+    /// allocating may collect any object that is not rooted, other
+    /// than the one being allocated and what it refers to.
+    pub fn auto_gc(mut self, enabled: bool) -> VmBuilder {
+        self.auto_gc = enabled;
+        self
+    }
+
+    /// Limit the estimated number of bytes occupied by the heap.  See
+    /// `VM::set_heap_limit`.
+    pub fn heap_limit(mut self, bytes: usize) -> VmBuilder {
+        self.heap_limit = Some(bytes);
+        self
+    }
+
+    /// Limit how deeply lists, vectors and quotes may be nested in
+    /// source text.  Deeper input fails to read with an error instead
+    /// of overflowing the stack.  The default is 256, which is safe
+    /// on threads with small stacks.
+    pub fn max_read_depth(mut self, depth: usize) -> VmBuilder {
+        self.max_read_depth = depth;
+        self
+    }
+
+    /// Create the configured VM.
+    pub fn build(self) -> VM {
+        let mut vm = VM::new();
+        vm.gc_threshold = self.gc_threshold;
+        vm.next_gc = self.gc_threshold;
+        vm.auto_gc = self.auto_gc;
+        vm.heap_limit = self.heap_limit;
+        vm.max_read_depth = self.max_read_depth;
+        vm
+    }
+}
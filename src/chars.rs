@@ -0,0 +1,49 @@
+use std::char::from_u32;
+use std::convert::TryFrom;
+
+use crate::{GObj, Object, VM};
+
+
+impl VM {
+    /// Convert a character to its Unicode scalar value.
+    pub fn char_to_int(&self, obj: impl GObj) -> Option<Object> {
+        Some(self.int(obj.as_char()? as isize))
+    }
+
+    /// Convert a Unicode scalar value to a character.  Returns `None`
+    /// for non-integers, and for integers that are not valid scalar
+    /// values, such as surrogates.
+    pub fn int_to_char(&self, obj: impl GObj) -> Option<Object> {
+        let code = u32::try_from(obj.as_int()?).ok()?;
+        Some(self.char(from_u32(code)?))
+    }
+
+    /// Convert a character to upper case.  Where the upper case form
+    /// is several characters, only the first is kept, so `ß` gives `S`.
+    pub fn char_upcase(&self, obj: impl GObj) -> Option<Object> {
+        let c = obj.as_char()?;
+        Some(self.char(c.to_uppercase().next().unwrap_or(c)))
+    }
+
+    /// Convert a character to lower case.  Where the lower case form
+    /// is several characters, only the first is kept, so `İ` gives `i`.
+    pub fn char_downcase(&self, obj: impl GObj) -> Option<Object> {
+        let c = obj.as_char()?;
+        Some(self.char(c.to_lowercase().next().unwrap_or(c)))
+    }
+
+    /// Check whether a character is alphabetic.
+    pub fn char_alphabetic(&self, obj: impl GObj) -> Option<bool> {
+        Some(obj.as_char()?.is_alphabetic())
+    }
+
+    /// Check whether a character is numeric.
+    pub fn char_numeric(&self, obj: impl GObj) -> Option<bool> {
+        Some(obj.as_char()?.is_numeric())
+    }
+
+    /// Check whether a character is whitespace.
+    pub fn char_whitespace(&self, obj: impl GObj) -> Option<bool> {
+        Some(obj.as_char()?.is_whitespace())
+    }
+}
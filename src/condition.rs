@@ -0,0 +1,37 @@
+use crate::{DObj, GinkgoError, GObj, HVal, Object, VM};
+
+
+impl VM {
+    /// Create and return a new error object with a message and a list
+    /// of irritants, the objects involved in the failure.
+    pub fn make_error(&mut self, message: String, irritants: &[Object]) -> Object {
+        self.alloc(HVal::Error { message, irritants: irritants.to_vec() })
+    }
+
+    /// Get the message of an error object.
+    pub fn error_message(&self, obj: impl GObj) -> Option<&str> {
+        match self.direct(obj) {
+            DObj::H(HVal::Error { message, .. }) => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Return a new list of the irritants of an error object.
+    pub fn error_irritants(&mut self, obj: impl GObj) -> Option<Object> {
+        let irritants = match self.direct(obj) {
+            DObj::H(HVal::Error { irritants, .. }) => irritants.clone(),
+            _ => return None,
+        };
+        Some(self.list(&irritants))
+    }
+
+    /// Check whether an object is an error object.
+    pub fn is_error(&self, obj: impl GObj) -> bool {
+        matches!(self.direct(obj), DObj::H(HVal::Error { .. }))
+    }
+
+    /// Create an error object describing a Rust-side error.
+    pub(crate) fn error_object(&mut self, err: &GinkgoError) -> Object {
+        self.alloc(HVal::Error { message: err.to_string(), irritants: Vec::new() })
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use broom::Handle;
+
+use crate::{GObj, HVal, Object, RootedObject, SVal, VM};
+
+
+/// Deep copier of object graphs from one VM to another.  Each source
+/// object is copied at most once, so shared structure and cycles are
+/// preserved.  Symbols are remapped by name, and copies keep the
+/// frozen flags and tags of the originals.
+struct Copier<'a> {
+    src: &'a VM,
+    copies: HashMap<Handle<HVal>, Handle<HVal>>,
+    pending: Vec<Handle<HVal>>,
+}
+
+impl<'a> Copier<'a> {
+    fn new(src: &'a VM) -> Copier<'a> {
+        Copier { src, copies: HashMap::new(), pending: Vec::new() }
+    }
+
+    /// Copy an object and everything reachable from it.  Returns
+    /// `None` if a dead object is reachable.
+    fn copy(&mut self, dst: &mut VM, obj: Object) -> Option<Object> {
+        let copy = self.copy_all(dst, obj);
+        if copy.is_none() {
+            // Unfinished copies still refer to objects in the source VM,
+            // so they must not be traced
+            for handle in self.pending.drain(..) {
+                *dst.heap.get_mut(handle).unwrap() = HVal::Vec(Rc::default());
+            }
+        }
+        copy
+    }
+
+    fn copy_all(&mut self, dst: &mut VM, obj: Object) -> Option<Object> {
+        let copy = self.shallow(dst, obj)?;
+        while let Some(handle) = self.pending.pop() {
+            // The copied value still refers to objects in the source VM.
+            let mut val = std::mem::replace(dst.heap.get_mut(handle).unwrap(), HVal::Vec(Rc::default()));
+            match &mut val {
+                HVal::Cons(car, cdr) => {
+                    *car = self.shallow(dst, *car)?;
+                    *cdr = self.shallow(dst, *cdr)?;
+                }
+                HVal::Vec(items) => {
+                    for item in Rc::make_mut(items).iter_mut() {
+                        *item = self.shallow(dst, *item)?;
+                    }
+                }
+                HVal::Env { vars, parent } => {
+                    *vars = vars.drain()
+                        .map(|(id, val)| Some((self.symbol(dst, id), self.shallow(dst, val)?)))
+                        .collect::<Option<_>>()?;
+                    if let Some(parent) = parent {
+                        *parent = self.shallow(dst, *parent)?;
+                    }
+                }
+                HVal::Closure { params, body, env } => {
+                    *params = self.shallow(dst, *params)?;
+                    *body = self.shallow(dst, *body)?;
+                    *env = self.shallow(dst, *env)?;
+                }
+                HVal::Error { irritants: items, .. } | HVal::Values(items) => {
+                    for item in items.iter_mut() {
+                        *item = self.shallow(dst, *item)?;
+                    }
+                }
+                HVal::Promise { thunk, value } => {
+                    *thunk = self.shallow(dst, *thunk)?;
+                    if let Some(value) = value {
+                        *value = self.shallow(dst, *value)?;
+                    }
+                }
+                HVal::String(_) | HVal::Rational(..) | HVal::Native(_) | HVal::Foreign(_) => {}
+                #[cfg(feature = "bignum")]
+                HVal::BigInt(_) => {}
+            }
+            *dst.heap.get_mut(handle).unwrap() = val;
+        }
+        Some(copy)
+    }
+
+    /// Map an object to its copy, allocating an unfinished copy if it
+    /// has not been seen before.  Allocation bypasses garbage
+    /// collection, since unfinished copies are not rooted.  Returns
+    /// `None` for dead objects.
+    fn shallow(&mut self, dst: &mut VM, obj: Object) -> Option<Object> {
+        match obj {
+            Object::S(SVal::Symbol(id)) => Some(Object::S(SVal::Symbol(self.symbol(dst, id)))),
+            Object::S(_) => Some(obj),
+            Object::H(handle) => {
+                if let Some(&copy) = self.copies.get(&handle) {
+                    return Some(Object::H(copy));
+                }
+                let val = self.src.heap.get(handle)?.clone();
+                let copy = dst.insert(val);
+                if self.src.frozen.contains(&handle) {
+                    dst.frozen.insert(copy);
+                }
+                if let Some(&tag) = self.src.tags.get(&handle) {
+                    dst.tags.insert(copy, tag);
+                }
+                self.copies.insert(handle, copy);
+                self.pending.push(copy);
+                Some(Object::H(copy))
+            }
+        }
+    }
+
+    fn symbol(&self, dst: &mut VM, id: usize) -> usize {
+        dst.intern(&self.src.symbols[id])
+    }
+}
+
+
+impl VM {
+    /// Create an independent VM with the same symbols and
+    /// configuration, holding deep copies of the given objects and
+    /// everything reachable from them, with the same frozen flags and
+    /// tags.
+    /// The heap cannot be enumerated, so the objects to carry over must
+    /// be named; they are returned rooted in the new VM, in the same
+    /// order.  Returns `None` if a dead object is reachable from them.
+    pub fn snapshot(&self, roots: &[Object]) -> Option<(VM, Vec<RootedObject>)> {
+        let mut vm = VM {
+            symbols: self.symbols.clone(),
+            symbol_ids: self.symbol_ids.clone(),
+            gc_threshold: self.gc_threshold,
+            next_gc: self.gc_threshold,
+            auto_gc: self.auto_gc,
+            heap_limit: self.heap_limit,
+            nil_is_false: self.nil_is_false,
+            undefined_display: self.undefined_display,
+            hide_undefined: self.hide_undefined,
+            print_radix: self.print_radix,
+            max_read_depth: self.max_read_depth,
+            ..VM::new()
+        };
+        let mut copier = Copier::new(self);
+        let mut copies = Vec::with_capacity(roots.len());
+        for &root in roots {
+            let copy = copier.copy(&mut vm, root)?;
+            copies.push(copy.root(&mut vm));
+        }
+        Some((vm, copies))
+    }
+
+    /// Deep-copy an object graph from another VM into this one,
+    /// returning the new object.  Stack values pass through unchanged,
+    /// except for symbols, which are remapped by name.  Copies keep the
+    /// frozen flags and tags of the originals.  The copy is unrooted.  Returns `None` if a dead object is reachable, in
+    /// which case the partial copy is left as garbage.
+    pub fn import(&mut self, other: &VM, obj: impl GObj) -> Option<Object> {
+        Copier::new(other).copy(self, obj.unroot())
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::{DObj, GObj, GinkgoError, HVal, Object, SVal, VM};
+
+
+impl VM {
+    /// Create and return a new unrooted environment frame, optionally
+    /// chained to a parent environment.
+    pub fn make_env(&mut self, parent: Option<Object>) -> Object {
+        self.alloc(HVal::Env { vars: HashMap::new(), parent })
+    }
+
+    /// Create and return a new unrooted environment frame, or fail if
+    /// the heap limit is exceeded.
+    pub fn try_make_env(&mut self, parent: Option<Object>) -> Result<Object, GinkgoError> {
+        self.try_alloc(HVal::Env { vars: HashMap::new(), parent })
+    }
+
+    /// Bind a symbol in the given environment frame, shadowing any
+    /// binding in parent environments.  Fails with
+    /// `GinkgoError::OutOfMemory`, leaving the frame unchanged, if a
+    /// new binding exceeds the heap limit.
+    pub fn env_define(
+        &mut self,
+        env: impl GObj,
+        sym: impl GObj,
+        val: impl GObj,
+    ) -> Result<(), GinkgoError> {
+        let id = symbol_id(sym.as_sval())?;
+        let env = env.unroot();
+        self.check_mutable(env)?;
+        let capacity = match self.direct_mut(env) {
+            DObj::H(HVal::Env { vars, .. }) => {
+                let capacity = vars.capacity();
+                if vars.insert(id, val.unroot()).is_some() {
+                    return Ok(());
+                }
+                capacity
+            }
+            _ => return Err(GinkgoError::TypeMismatch("environment")),
+        };
+        self.grown(env, |val| if let HVal::Env { vars, .. } = val {
+            vars.remove(&id);
+            vars.shrink_to(capacity);
+        })
+    }
+
+    /// Look up the value of a symbol in an environment or its parents.
+    pub fn env_lookup(&self, env: impl GObj, sym: impl GObj) -> Option<Object> {
+        let id = symbol_id(sym.as_sval()).ok()?;
+        let mut env = env.unroot();
+        loop {
+            match self.direct(env) {
+                DObj::H(HVal::Env { vars, parent }) => match vars.get(&id) {
+                    Some(val) => return Some(*val),
+                    None => env = (*parent)?,
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// Change the value of a symbol in the nearest environment where it
+    /// is bound.  Fails if the symbol is unbound.
+    pub fn env_set(
+        &mut self,
+        env: impl GObj,
+        sym: impl GObj,
+        val: impl GObj,
+    ) -> Result<(), GinkgoError> {
+        let id = symbol_id(sym.as_sval())?;
+        let mut env = env.unroot();
+        loop {
+            let frozen = self.is_frozen(env);
+            match self.direct_mut(env) {
+                DObj::H(HVal::Env { vars, parent }) => match vars.get_mut(&id) {
+                    Some(_) if frozen => return Err(GinkgoError::Immutable),
+                    Some(slot) => {
+                        *slot = val.unroot();
+                        return Ok(());
+                    }
+                    None => match parent {
+                        Some(parent) => env = *parent,
+                        None => return Err(GinkgoError::Unbound(self.symbols[id].clone())),
+                    },
+                },
+                _ => return Err(GinkgoError::TypeMismatch("environment")),
+            }
+        }
+    }
+}
+
+
+fn symbol_id(sym: Option<SVal>) -> Result<usize, GinkgoError> {
+    match sym {
+        Some(SVal::Symbol(id)) => Ok(id),
+        _ => Err(GinkgoError::TypeMismatch("symbol")),
+    }
+}
@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{DObj, GObj, HVal, Object, SVal, VM};
+
+
+/// Maximum number of nodes visited when hashing a structure.
+const HASH_BUDGET: usize = 64;
+
+
+/// Object wrapper with structural `Hash` and `Eq`, as by `VM::hash`
+/// and `VM::equal`, for use as a key in Rust collections.  Like
+/// floating point numbers, keys containing NaN are not equal to
+/// themselves.
+#[derive(Clone, Copy)]
+pub struct Keyed<'a> {
+    vm: &'a VM,
+    object: Object,
+}
+
+impl Keyed<'_> {
+    /// Get the wrapped object.
+    pub fn object(&self) -> Object {
+        self.object
+    }
+}
+
+impl PartialEq for Keyed<'_> {
+    fn eq(&self, other: &Keyed) -> bool {
+        self.vm.equal(self.object, other.object)
+    }
+}
+
+impl Eq for Keyed<'_> {}
+
+impl Hash for Keyed<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.vm.hash(self.object));
+    }
+}
+
+
+impl VM {
+    /// Check two objects for structural equality, like Scheme's
+    /// `equal?`.  Conses and vectors are compared element by element
+    /// and strings by content.  Numbers are equal only if they have the
+    /// same exactness and value.  Cyclic structures are handled, and
+    /// compare equal if they have the same shape.
+    pub fn equal(&self, a: impl GObj, b: impl GObj) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![(a.unroot(), b.unroot())];
+
+        while let Some((a, b)) = stack.pop() {
+            let (ha, hb) = match (a, b) {
+                (Object::S(l), Object::S(r)) if l == r => continue,
+                (Object::H(l), Object::H(r)) if l == r => continue,
+                (Object::H(l), Object::H(r)) => (l, r),
+                _ => return false,
+            };
+
+            match (self.direct(a), self.direct(b)) {
+                (DObj::H(HVal::Cons(lcar, lcdr)), DObj::H(HVal::Cons(rcar, rcdr))) => {
+                    if visited.insert((ha, hb)) {
+                        stack.push((*lcdr, *rcdr));
+                        stack.push((*lcar, *rcar));
+                    }
+                }
+                (DObj::H(HVal::Vec(l)), DObj::H(HVal::Vec(r))) => {
+                    if l.len() != r.len() {
+                        return false;
+                    }
+                    if visited.insert((ha, hb)) {
+                        stack.extend(l.iter().copied().zip(r.iter().copied()).rev());
+                    }
+                }
+                (DObj::H(HVal::String(l)), DObj::H(HVal::String(r))) if l == r => (),
+                (DObj::H(HVal::Rational(ln, ld)), DObj::H(HVal::Rational(rn, rd))) if (ln, ld) == (rn, rd) => (),
+                #[cfg(feature = "bignum")]
+                (DObj::H(HVal::BigInt(l)), DObj::H(HVal::BigInt(r))) if l == r => (),
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Check two objects for equivalence, like Scheme's `eqv?`.  Stack
+    /// values are compared by value, as are exact numbers stored on
+    /// the heap.  Other heap objects are only equivalent to themselves,
+    /// so separately built conses, vectors and strings are never
+    /// equivalent even when they are `equal`.  Floats are compared by
+    /// their bits, so `0.0` and `-0.0` are not equivalent, while NaN is
+    /// equivalent to itself.
+    pub fn eqv(&self, a: impl GObj, b: impl GObj) -> bool {
+        let (a, b) = (a.unroot(), b.unroot());
+        if let (Some(SVal::Float(l)), Some(SVal::Float(r))) = (a.as_sval(), b.as_sval()) {
+            return l.to_bits() == r.to_bits();
+        }
+        if a == b {
+            return true;
+        }
+        match (self.direct(a), self.direct(b)) {
+            (DObj::H(HVal::Rational(ln, ld)), DObj::H(HVal::Rational(rn, rd))) => (ln, ld) == (rn, rd),
+            #[cfg(feature = "bignum")]
+            (DObj::H(HVal::BigInt(l)), DObj::H(HVal::BigInt(r))) => l == r,
+            _ => false,
+        }
+    }
+
+    /// Order two objects.  Numbers are ordered by value, mixing exact
+    /// and inexact numbers, and strings lexicographically.  Returns
+    /// `None` for other types, for objects of different types, and
+    /// when either number is NaN.
+    pub fn compare(&self, a: impl GObj, b: impl GObj) -> Option<Ordering> {
+        let (a, b) = (a.unroot(), b.unroot());
+        match (self.direct(a), self.direct(b)) {
+            (DObj::H(HVal::String(l)), DObj::H(HVal::String(r))) => Some(l.cmp(r)),
+            _ => self.num_cmp(a, b),
+        }
+    }
+
+    /// Wrap an object for use as a key in Rust collections, with
+    /// structural hashing and equality.
+    pub fn keyed(&self, obj: impl GObj) -> Keyed<'_> {
+        Keyed { vm: self, object: obj.unroot() }
+    }
+
+    /// Compute a structural hash of an object, consistent with
+    /// `equal`: objects that are `equal` have the same hash.  Only a
+    /// bounded number of nodes are visited, so hashing large or cyclic
+    /// structures is cheap.
+    pub fn hash(&self, obj: impl GObj) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let mut stack = vec![obj.unroot()];
+        let mut budget = HASH_BUDGET;
+
+        while let Some(obj) = stack.pop() {
+            if budget == 0 {
+                break;
+            }
+            budget -= 1;
+
+            match self.direct(obj) {
+                DObj::D(_) => 0u8.hash(&mut hasher),
+                DObj::S(SVal::Undefined) => 1u8.hash(&mut hasher),
+                DObj::S(SVal::Nil) => 2u8.hash(&mut hasher),
+                DObj::S(SVal::Bool(v)) => (3u8, v).hash(&mut hasher),
+                DObj::S(SVal::Int(v)) => (4u8, v).hash(&mut hasher),
+                DObj::S(SVal::Float(v)) => (5u8, (v + 0.0).to_bits()).hash(&mut hasher),
+                DObj::S(SVal::Symbol(id)) => (12u8, id).hash(&mut hasher),
+                DObj::S(SVal::Char(c)) => (15u8, c).hash(&mut hasher),
+                DObj::H(HVal::Cons(car, cdr)) => {
+                    6u8.hash(&mut hasher);
+                    stack.push(*cdr);
+                    stack.push(*car);
+                }
+                DObj::H(HVal::Vec(vec)) => {
+                    (7u8, vec.len()).hash(&mut hasher);
+                    stack.extend(vec.iter().rev());
+                }
+                DObj::H(HVal::String(s)) => (8u8, s).hash(&mut hasher),
+                DObj::H(HVal::Rational(n, d)) => (9u8, n, d).hash(&mut hasher),
+                #[cfg(feature = "bignum")]
+                DObj::H(HVal::BigInt(v)) => (10u8, v).hash(&mut hasher),
+                DObj::H(HVal::Native(_)) => 11u8.hash(&mut hasher),
+                DObj::H(HVal::Foreign(_)) => 16u8.hash(&mut hasher),
+                DObj::H(HVal::Env { .. }) => 13u8.hash(&mut hasher),
+                DObj::H(HVal::Closure { .. }) => 14u8.hash(&mut hasher),
+                DObj::H(HVal::Error { .. }) => 17u8.hash(&mut hasher),
+                DObj::H(HVal::Values(_)) => 18u8.hash(&mut hasher),
+                DObj::H(HVal::Promise { .. }) => 19u8.hash(&mut hasher),
+            }
+        }
+
+        hasher.finish()
+    }
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::fmt;
+
+
+/// Error raised by fallible VM operations.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GinkgoError {
+    /// An object had the wrong type; holds the expected type.
+    TypeMismatch(&'static str),
+    /// A variable was not bound; holds its name.
+    Unbound(String),
+    /// An expression could not be evaluated; holds a description.
+    Syntax(String),
+    /// A procedure was called with the wrong number of arguments.
+    Arity { expected: usize, given: usize },
+    /// A procedure with rest parameters was called with too few
+    /// arguments.
+    ArityAtLeast { expected: usize, given: usize },
+    /// An allocation would exceed the heap limit.
+    OutOfMemory,
+    /// An index was out of bounds; holds the index, the length and the
+    /// kind of object that was indexed.
+    IndexOutOfBounds { index: usize, len: usize, kind: &'static str },
+    /// A frozen object was mutated.
+    Immutable,
+}
+
+impl fmt::Display for GinkgoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GinkgoError::TypeMismatch(expected) => write!(f, "expected {}", expected),
+            GinkgoError::Unbound(name) => write!(f, "unbound variable: {}", name),
+            GinkgoError::Syntax(msg) => write!(f, "syntax error: {}", msg),
+            GinkgoError::Arity { expected, given } =>
+                write!(f, "expected {} arguments, got {}", expected, given),
+            GinkgoError::ArityAtLeast { expected, given } =>
+                write!(f, "expected at least {} arguments, got {}", expected, given),
+            GinkgoError::OutOfMemory => write!(f, "heap limit exceeded"),
+            GinkgoError::IndexOutOfBounds { index, len, kind } =>
+                write!(f, "index {} out of range for {} of length {}", index, kind, len),
+            GinkgoError::Immutable => write!(f, "cannot mutate a frozen object"),
+        }
+    }
+}
+
+impl Error for GinkgoError {}
@@ -0,0 +1,223 @@
+use crate::{DObj, GObj, GinkgoError, HVal, Object, SVal, VM};
+
+
+impl VM {
+    /// Evaluate an expression in an environment.  Symbols are looked up
+    /// in the environment, and all other atoms evaluate to themselves.
+    /// Lists are evaluated as special forms or procedure calls.
+    ///
+    /// Expressions in tail position (the branches of `if`, the last
+    /// form of `begin` and the last form of a procedure body) are
+    /// evaluated without growing the Rust stack, so tail calls may
+    /// recurse indefinitely.
+    pub fn eval(&mut self, expr: impl GObj, env: impl GObj) -> Result<Object, GinkgoError> {
+        let mut expr = expr.unroot();
+        let mut env = env.unroot();
+
+        // Keep the expression and environment alive.  After a tail
+        // call, these are the frame and the procedure whose body is
+        // being evaluated.
+        let mut _rooted = (env.root(self), expr.root(self));
+
+        loop {
+            let (head, rest) = match self.direct(expr) {
+                DObj::S(SVal::Symbol(id)) => return self.env_lookup(env, expr)
+                    .ok_or_else(|| GinkgoError::Unbound(self.symbols[id].clone())),
+                DObj::H(&HVal::Cons(head, rest)) => (head, rest),
+                _ => return Ok(expr),
+            };
+            let args = self.list_to_vec(rest).ok_or_else(|| syntax("improper form"))?;
+
+            match self.symbol_name(head) {
+                Some("quote") => return match args[..] {
+                    [datum] => Ok(datum),
+                    _ => Err(syntax("quote expects exactly one argument")),
+                },
+                Some("if") => {
+                    let (test, then, otherwise) = match args[..] {
+                        [test, then] => (test, then, None),
+                        [test, then, otherwise] => (test, then, Some(otherwise)),
+                        _ => return Err(syntax("if expects two or three arguments")),
+                    };
+                    let test = self.eval_single(test, env)?;
+                    expr = match otherwise {
+                        _ if self.is_truthy(test) => then,
+                        Some(otherwise) => otherwise,
+                        None => return Ok(Object::Undef),
+                    };
+                }
+                Some("begin") => match args.split_last() {
+                    Some((last, init)) => {
+                        for expr in init {
+                            self.eval(*expr, env)?;
+                        }
+                        expr = *last;
+                    }
+                    None => return Ok(Object::Undef),
+                },
+                Some("lambda") => {
+                    if args.len() < 2 {
+                        return Err(syntax("lambda expects parameters and a body"));
+                    }
+                    return self.make_closure(args[0], self.cdr(rest).unwrap(), env);
+                }
+                Some("delay") => return match args[..] {
+                    [_] => {
+                        let thunk = self.make_closure(Object::Nil, rest, env)?;
+                        self.try_alloc(HVal::Promise { thunk, value: None })
+                    }
+                    _ => Err(syntax("delay expects exactly one argument")),
+                },
+                Some("define") => return self.eval_define(&args, rest, env),
+                Some("set!") => return match args[..] {
+                    [name, expr] if self.symbol_name(name).is_some() => {
+                        let value = self.eval_single(expr, env)?;
+                        self.env_set(env, name, value)?;
+                        Ok(Object::Undef)
+                    }
+                    _ => Err(syntax("set! expects a name and a value")),
+                },
+                _ => {
+                    // Evaluating an argument may collect garbage, so the
+                    // procedure and the values so far are rooted
+                    let proc = self.eval_single(head, env)?.root(self);
+                    let mut rooted = Vec::with_capacity(args.len());
+                    for arg in args {
+                        rooted.push(self.eval_single(arg, env)?.root(self));
+                    }
+                    let values: Vec<Object> = rooted.iter().map(GObj::unroot).collect();
+                    match self.direct(proc.unroot()) {
+                        DObj::H(HVal::Closure { .. }) => {
+                            let (tail, frame) = self.enter_closure(proc.unroot(), &values)?;
+                            expr = tail;
+                            env = frame;
+                            _rooted = (frame.root(self), proc);
+                        }
+                        _ => return self.apply_proc(proc.unroot(), &values),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Evaluate an expression in a context expecting a single value,
+    /// keeping only the first of multiple values.
+    fn eval_single(&mut self, expr: Object, env: Object) -> Result<Object, GinkgoError> {
+        let value = self.eval(expr, env)?;
+        Ok(self.first_value(value))
+    }
+
+    /// Evaluate an expression like `eval`, but return failures as
+    /// error objects instead of Rust errors.  Errors are values, and
+    /// can be told apart from other results with `is_error`.
+    pub fn eval_catch(&mut self, expr: impl GObj, env: impl GObj) -> Object {
+        match self.eval(expr, env) {
+            Ok(value) => value,
+            Err(err) => self.error_object(&err),
+        }
+    }
+
+    /// Call a procedure with the given arguments.  Returns `None` if
+    /// the object is not a procedure, or if the call fails.
+    pub fn apply(&mut self, proc: impl GObj, args: &[Object]) -> Option<Object> {
+        self.apply_proc(proc.unroot(), args).ok()
+    }
+
+    /// Call a procedure with the elements of a list as arguments, like
+    /// Scheme's `apply`.  Returns `None` if the list is improper, if
+    /// the object is not a procedure, or if the call fails.
+    pub fn apply_list(&mut self, proc: impl GObj, args: impl GObj) -> Option<Object> {
+        let args = self.list_to_vec(args)?;
+        self.apply(proc, &args)
+    }
+
+    fn eval_define(&mut self, args: &[Object], rest: Object, env: Object) -> Result<Object, GinkgoError> {
+        match self.direct(args.first().copied().unwrap_or(Object::Nil)) {
+            DObj::S(SVal::Symbol(_)) => match *args {
+                [name, expr] => {
+                    let value = self.eval_single(expr, env)?;
+                    self.env_define(env, name, value)?;
+                    Ok(Object::Undef)
+                }
+                _ => Err(syntax("define expects a name and a value")),
+            },
+            DObj::H(&HVal::Cons(name, params)) if args.len() >= 2 => {
+                if self.symbol_name(name).is_none() {
+                    return Err(syntax("procedure name must be a symbol"));
+                }
+                let closure = self.make_closure(params, self.cdr(rest).unwrap(), env)?;
+                self.env_define(env, name, closure)?;
+                Ok(Object::Undef)
+            }
+            _ => Err(syntax("malformed define")),
+        }
+    }
+
+    /// Create a closure.  The parameters are a list of symbols,
+    /// possibly with a symbol as its tail, which is bound to a list of
+    /// the remaining arguments.
+    fn make_closure(&mut self, params: Object, body: Object, env: Object) -> Result<Object, GinkgoError> {
+        let (names, rest) = self.list_parts(params);
+        if rest != Object::Nil && self.symbol_name(rest).is_none() {
+            return Err(syntax("improper parameter list"));
+        }
+        if !names.iter().all(|p| self.symbol_name(*p).is_some()) {
+            return Err(syntax("parameters must be symbols"));
+        }
+        self.try_alloc(HVal::Closure { params, body, env })
+    }
+
+    fn apply_proc(&mut self, proc: Object, args: &[Object]) -> Result<Object, GinkgoError> {
+        let _proc = proc.root(self);
+        let _args: Vec<_> = args.iter().map(|arg| arg.root(self)).collect();
+        match self.direct(proc) {
+            DObj::H(&HVal::Native(f)) => Ok(f(self, args)),
+            DObj::H(HVal::Closure { .. }) => {
+                let (tail, frame) = self.enter_closure(proc, args)?;
+                self.eval(tail, frame)
+            }
+            _ => Err(GinkgoError::TypeMismatch("procedure")),
+        }
+    }
+
+    /// Bind the arguments of a closure call in a new frame, and
+    /// evaluate all but the last form of the body.  Returns the last
+    /// form and the frame to evaluate it in.  The caller must keep the
+    /// procedure and the arguments rooted.
+    fn enter_closure(&mut self, proc: Object, args: &[Object]) -> Result<(Object, Object), GinkgoError> {
+        let (params, body, env) = match self.direct(proc) {
+            DObj::H(&HVal::Closure { params, body, env }) => (params, body, env),
+            _ => return Err(GinkgoError::TypeMismatch("procedure")),
+        };
+
+        let (params, rest) = self.list_parts(params);
+        let (expected, given) = (params.len(), args.len());
+        match rest {
+            Object::Nil if given != expected => return Err(GinkgoError::Arity { expected, given }),
+            _ if given < expected => return Err(GinkgoError::ArityAtLeast { expected, given }),
+            _ => {}
+        }
+
+        let frame = self.try_make_env(Some(env))?;
+        let _rooted = frame.root(self);
+        for (param, arg) in params.into_iter().zip(args) {
+            self.env_define(frame, param, *arg)?;
+        }
+        if rest != Object::Nil {
+            let list = self.try_prepend(&args[expected..], Object::Nil)?;
+            self.env_define(frame, rest, list)?;
+        }
+
+        let body = self.list_to_vec(body).ok_or_else(|| syntax("malformed lambda body"))?;
+        let (last, init) = body.split_last().ok_or_else(|| syntax("malformed lambda body"))?;
+        for expr in init {
+            self.eval(*expr, frame)?;
+        }
+        Ok((*last, frame))
+    }
+}
+
+
+fn syntax(msg: &str) -> GinkgoError {
+    GinkgoError::Syntax(msg.to_string())
+}
@@ -0,0 +1,40 @@
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{DObj, GObj, HVal, Object, VM};
+
+
+/// Opaque Rust value stored in a Ginkgo object.  Compares by identity.
+#[derive(Clone)]
+pub struct Foreign(Rc<dyn Any>);
+
+impl PartialEq for Foreign {
+    fn eq(&self, other: &Foreign) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Foreign {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Foreign({:p})", Rc::as_ptr(&self.0))
+    }
+}
+
+
+impl VM {
+    /// Create and return a new unrooted object wrapping a Rust value.
+    /// Copying the object to another VM shares the value.
+    pub fn foreign(&mut self, v: Box<dyn Any>) -> Object {
+        self.alloc(HVal::Foreign(Foreign(Rc::from(v))))
+    }
+
+    /// Get a reference to the Rust value in a foreign object.  Returns
+    /// `None` if the object is not foreign or holds a different type.
+    pub fn foreign_ref<T: 'static>(&self, obj: impl GObj) -> Option<&T> {
+        match self.direct(obj) {
+            DObj::H(HVal::Foreign(Foreign(v))) => v.downcast_ref(),
+            _ => None,
+        }
+    }
+}
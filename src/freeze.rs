@@ -0,0 +1,32 @@
+use crate::{GinkgoError, GObj, Object, VM};
+
+
+impl VM {
+    /// Mark an object as immutable.  Mutating operations such as
+    /// `vec_set`, `set_car` and `env_define` fail on a frozen object
+    /// with `GinkgoError::Immutable`.  Freezing is shallow: objects
+    /// referred to by a frozen object are not frozen.  Stack values
+    /// are immutable anyway, and are ignored.
+    pub fn freeze(&mut self, obj: impl GObj) {
+        if let Object::H(handle) = obj.unroot() {
+            self.frozen.insert(handle);
+        }
+    }
+
+    /// Check whether an object has been frozen.
+    pub fn is_frozen(&self, obj: impl GObj) -> bool {
+        match obj.unroot() {
+            Object::H(handle) => self.frozen.contains(&handle),
+            Object::S(_) => false,
+        }
+    }
+
+    /// Fail if an object is frozen.
+    pub(crate) fn check_mutable(&self, obj: Object) -> Result<(), GinkgoError> {
+        if self.is_frozen(obj) {
+            Err(GinkgoError::Immutable)
+        } else {
+            Ok(())
+        }
+    }
+}
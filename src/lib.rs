@@ -1,4 +1,6 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::rc::Rc;
 
 use broom::{Heap, Handle, Rooted};
 use broom::prelude::{Trace, Tracer};
@@ -6,7 +8,38 @@ use broom::prelude::{Trace, Tracer};
 #[cfg(test)]
 mod test;
 
+mod access;
+mod builder;
+mod chars;
+mod condition;
+mod copy;
+mod env;
+mod equal;
+mod error;
+mod eval;
+mod freeze;
+mod foreign;
+mod list;
+mod num;
+mod promise;
+mod read;
+mod send;
+mod size;
 mod string;
+mod tag;
+mod values;
+mod vector;
+mod weak;
+
+pub use access::Ref;
+pub use builder::VmBuilder;
+pub use equal::Keyed;
+pub use error::GinkgoError;
+pub use foreign::Foreign;
+pub use list::ListIter;
+pub use read::{ReadError, ReadOutcome, Reader};
+pub use send::SendableVm;
+pub use weak::WeakObject;
 
 
 /// Stack-based Ginkgo value.
@@ -17,16 +50,53 @@ pub enum SVal {
     Bool(bool),
     Int(isize),
     Float(f64),
+    Symbol(usize),
+    Char(char),
 }
 
 /// Heap-based (garbage-collected) Ginkgo value.
 #[derive(Clone, Debug, PartialEq)]
+#[allow(unpredictable_function_pointer_comparisons)]
 pub enum HVal {
     Cons(Object, Object),
-    Vec(Vec<Object>),
+    /// Vector elements, possibly shared with other vectors until one
+    /// of them is mutated.
+    Vec(Rc<Vec<Object>>),
     String(String),
+    Rational(isize, isize),
+    #[cfg(feature = "bignum")]
+    BigInt(num_bigint::BigInt),
+    Native(NativeFn),
+    Foreign(Foreign),
+    Env {
+        vars: HashMap<usize, Object>,
+        parent: Option<Object>,
+    },
+    Closure {
+        params: Object,
+        body: Object,
+        env: Object,
+    },
+    /// Condition object describing a failure.
+    Error {
+        message: String,
+        irritants: Vec<Object>,
+    },
+    /// Multiple return values, other than exactly one.
+    Values(Vec<Object>),
+    /// Delayed computation, holding the result once forced.
+    Promise {
+        thunk: Object,
+        value: Option<Object>,
+    },
 }
 
+/// Signature of a Rust function callable as a Ginkgo procedure.
+pub type NativeFn = fn(&mut VM, &[Object]) -> Object;
+
+/// Function called with every value allocated on the heap.
+type AllocHook = dyn FnMut(&HVal);
+
 /// Safe Ginkgo object.  Either a direct representation of a stack
 /// value or a GC-handle to a heap value.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -80,6 +150,13 @@ pub trait GObj {
             _ => None,
         }
     }
+
+    fn as_char(&self) -> Option<char> {
+        match self.as_sval()? {
+            SVal::Char(v) => Some(v),
+            _ => None,
+        }
+    }
 }
 
 impl GObj for Object {
@@ -139,9 +216,34 @@ impl Trace<HVal> for HVal {
                 car.trace(tracer);
                 cdr.trace(tracer);
             },
-            HVal::Vec(vec) => for obj in vec {
+            HVal::Vec(vec) => for obj in vec.iter() {
+                obj.trace(tracer);
+            }
+            HVal::Env { vars, parent } => {
+                for obj in vars.values() {
+                    obj.trace(tracer);
+                }
+                if let Some(parent) = parent {
+                    parent.trace(tracer);
+                }
+            }
+            HVal::Closure { params, body, env } => {
+                params.trace(tracer);
+                body.trace(tracer);
+                env.trace(tracer);
+            }
+            HVal::Error { irritants, .. } => for obj in irritants {
+                obj.trace(tracer);
+            }
+            HVal::Values(values) => for obj in values {
                 obj.trace(tracer);
             }
+            HVal::Promise { thunk, value } => {
+                thunk.trace(tracer);
+                if let Some(value) = value {
+                    value.trace(tracer);
+                }
+            }
             _ => (),
         }
     }
@@ -190,51 +292,147 @@ impl Object {
 pub struct WrappedObject<'a> {
     vm: &'a VM,
     object: Object,
+    /// Number of characters of each string to print, if limited.
+    max_string_len: Option<usize>,
+}
+
+/// Pending output while printing an object.
+enum Print {
+    /// An object to print.
+    Object(Object),
+    /// The rest of a list, after its first element.
+    Tail(Object),
+    /// Literal text.
+    Text(&'static str),
+}
+
+/// Schedule the printing of a sequence of objects separated by
+/// spaces, optionally with a leading space.
+fn print_items(stack: &mut Vec<Print>, items: &[Object], leading: bool) {
+    for (i, item) in items.iter().enumerate().rev() {
+        stack.push(Print::Object(*item));
+        if i > 0 || leading {
+            stack.push(Print::Text(" "));
+        }
+    }
 }
 
 impl fmt::Display for WrappedObject<'_> {
+    /// Nested structure is printed with an explicit stack instead of
+    /// by recursion, so that deep nesting cannot overflow the Rust
+    /// stack.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.vm.direct(self.object) {
-            DObj::D(ptr) => write!(f, "#dead<{:?}>", ptr),
-            DObj::S(SVal::Undefined) => write!(f, "#undefined"),
-            DObj::S(SVal::Nil) => write!(f, "nil"),
-            DObj::S(SVal::Bool(true)) => write!(f, "#t"),
-            DObj::S(SVal::Bool(false)) => write!(f, "#f"),
-            DObj::S(SVal::Int(v)) => write!(f, "{}", v),
-            DObj::S(SVal::Float(v)) => {
-                let s = format!("{}", v);
-                match s.find('.') {
-                    Some(_) => write!(f, "{}", s),
-                    None => write!(f, "{}.0", s),
+        let mut stack = vec![Print::Object(self.object)];
+        let mut top = true;
+
+        while let Some(item) = stack.pop() {
+            let obj = match item {
+                Print::Object(obj) => obj,
+                Print::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
                 }
-            }
-            DObj::H(&HVal::Cons(car, cdr)) => {
-                write!(f, "({}", self.vm.wrap(car))?;
-
-                let mut tail: Object = cdr;
-                while let DirectObject::H(&HVal::Cons(car, cdr)) = self.vm.direct(tail) {
-                    write!(f, " {}", self.vm.wrap(car))?;
-                    tail = cdr;
+                Print::Tail(tail) => {
+                    match self.vm.direct(tail) {
+                        DObj::H(&HVal::Cons(car, cdr)) => {
+                            f.write_str(" ")?;
+                            stack.push(Print::Tail(cdr));
+                            stack.push(Print::Object(car));
+                        }
+                        DObj::S(SVal::Nil) => f.write_str(")")?,
+                        _ => {
+                            f.write_str(" . ")?;
+                            stack.push(Print::Text(")"));
+                            stack.push(Print::Object(tail));
+                        }
+                    }
+                    continue;
                 }
-
-                if tail == Object::Nil {
-                    write!(f, ")")
-                } else {
-                    write!(f, " . {})", self.vm.wrap(tail))
+            };
+
+            let nested = !std::mem::replace(&mut top, false);
+
+            match self.vm.direct(obj) {
+                DObj::D(ptr) => write!(f, "#dead<{:?}>", ptr)?,
+                DObj::S(SVal::Undefined) if nested && self.vm.hide_undefined => f.write_str("_")?,
+                DObj::S(SVal::Undefined) => f.write_str(self.vm.undefined_display)?,
+                DObj::S(SVal::Nil) => write!(f, "nil")?,
+                DObj::S(SVal::Bool(true)) => write!(f, "#t")?,
+                DObj::S(SVal::Bool(false)) => write!(f, "#f")?,
+                DObj::S(SVal::Int(v)) => match self.vm.print_radix {
+                    10 => write!(f, "{}", v)?,
+                    radix => {
+                        let prefix = num::radix_prefix(radix).unwrap_or_default();
+                        write!(f, "{}{}", prefix, num::int_to_radix(v, radix))?
+                    }
+                },
+                DObj::S(SVal::Symbol(id)) => match &self.vm.symbols[id] {
+                    name if read::is_plain_symbol(name) => write!(f, "{}", name)?,
+                    name => write!(f, "|{}|", string::escape_symbol(name))?,
+                },
+                DObj::S(SVal::Char(c)) => match string::char_name(c) {
+                    Some(name) => write!(f, "#\\{}", name)?,
+                    None if c.is_control() => write!(f, "#\\x{:x}", c as u32)?,
+                    None => write!(f, "#\\{}", c)?,
+                },
+                DObj::S(SVal::Float(v)) if v.is_nan() => write!(f, "+nan.0")?,
+                DObj::S(SVal::Float(v)) if v.is_infinite() => write!(f, "{}inf.0", if v > 0.0 { '+' } else { '-' })?,
+                DObj::S(SVal::Float(v)) => {
+                    let s = format!("{}", v);
+                    match s.find('.') {
+                        Some(_) => write!(f, "{}", s)?,
+                        None => write!(f, "{}.0", s)?,
+                    }
                 }
-            }
-            DObj::H(HVal::Vec(vec)) => {
-                write!(f, "#(")?;
-                if vec.len() > 0 {
-                    write!(f, "{}", self.vm.wrap(vec[0]))?;
+                DObj::H(&HVal::Cons(car, cdr)) => {
+                    write!(f, "(")?;
+                    stack.push(Print::Tail(cdr));
+                    stack.push(Print::Object(car));
+                }
+                DObj::H(HVal::Vec(vec)) => {
+                    write!(f, "#(")?;
+                    stack.push(Print::Text(")"));
+                    print_items(&mut stack, vec, false);
+                }
+                DObj::H(HVal::String(s)) => {
+                    let cut = self.max_string_len.and_then(|max| s.char_indices().nth(max));
+                    match cut {
+                        Some((i, _)) => {
+                            let elided = s[i..].chars().count();
+                            write!(f, "\"{}...<{} more chars>\"", string::escape(&s[..i]), elided)?
+                        }
+                        None => write!(f, "\"{}\"", string::escape(s))?,
+                    }
                 }
-                for obj in &vec[1..] {
-                    write!(f, " {}", self.vm.wrap(*obj))?;
+                DObj::H(HVal::Rational(n, d)) => write!(f, "{}/{}", n, d)?,
+                #[cfg(feature = "bignum")]
+                DObj::H(HVal::BigInt(v)) => {
+                    let radix = self.vm.print_radix;
+                    write!(f, "{}{}", num::radix_prefix(radix).unwrap_or_default(), v.to_str_radix(radix))?
                 }
-                write!(f, ")")
+                DObj::H(HVal::Native(_)) => write!(f, "#<native>")?,
+                DObj::H(HVal::Foreign(_)) => write!(f, "#<foreign>")?,
+                DObj::H(HVal::Env { .. }) => write!(f, "#<environment>")?,
+                DObj::H(HVal::Closure { .. }) => write!(f, "#<procedure>")?,
+                DObj::H(HVal::Error { message, irritants }) => {
+                    write!(f, "#<error: {}", message)?;
+                    stack.push(Print::Text(">"));
+                    if !irritants.is_empty() {
+                        write!(f, " (")?;
+                        stack.push(Print::Text(")"));
+                        print_items(&mut stack, irritants, false);
+                    }
+                }
+                DObj::H(HVal::Values(values)) => {
+                    write!(f, "#<values")?;
+                    stack.push(Print::Text(">"));
+                    print_items(&mut stack, values, true);
+                }
+                DObj::H(HVal::Promise { .. }) => write!(f, "#<promise>")?,
             }
-            DObj::H(HVal::String(s)) => write!(f, "\"{}\"", string::escape(s))
         }
+
+        Ok(())
     }
 }
 
@@ -242,17 +440,119 @@ impl fmt::Display for WrappedObject<'_> {
 /// The Ginkgo virtual machine.  Entry point for all use of Ginkgo.
 pub struct VM {
     heap: Heap<HVal>,
+    symbols: Vec<String>,
+    symbol_ids: HashMap<String, usize>,
+    gc_threshold: usize,
+    next_gc: usize,
+    auto_gc: bool,
+    heap_limit: Option<usize>,
+    heap_bytes: usize,
+    sizes: HashMap<Handle<HVal>, usize>,
+    alloc_hook: Option<Box<AllocHook>>,
+    weaks: HashMap<usize, Handle<HVal>>,
+    next_weak: usize,
+    frozen: HashSet<Handle<HVal>>,
+    tags: HashMap<Handle<HVal>, u32>,
+    nil_is_false: bool,
+    undefined_display: &'static str,
+    hide_undefined: bool,
+    print_radix: u32,
+    max_read_depth: usize,
+}
+
+/// The default VM is the same as the one returned by `VM::new()`.
+impl Default for VM {
+    fn default() -> VM {
+        VM::new()
+    }
 }
 
 impl VM {
-    /// Create and return a new VM.
+    /// Create and return a new VM with the default configuration.
+    /// Use `VmBuilder` to configure it.  Equivalent to `VM::default()`.
     pub fn new() -> VM {
-        VM { heap: Heap::default() }
+        VM {
+            heap: Heap::default(),
+            symbols: Vec::new(),
+            symbol_ids: HashMap::new(),
+            gc_threshold: builder::DEFAULT_GC_THRESHOLD,
+            next_gc: builder::DEFAULT_GC_THRESHOLD,
+            auto_gc: false,
+            heap_limit: None,
+            heap_bytes: 0,
+            sizes: HashMap::new(),
+            alloc_hook: None,
+            weaks: HashMap::new(),
+            next_weak: 0,
+            frozen: HashSet::new(),
+            tags: HashMap::new(),
+            nil_is_false: false,
+            undefined_display: "#undefined",
+            hide_undefined: false,
+            print_radix: 10,
+            max_read_depth: builder::DEFAULT_MAX_READ_DEPTH,
+        }
     }
 
     /// Perform a garbage collection.
     pub fn gc(&mut self) {
         self.heap.clean();
+        let heap = &self.heap;
+        self.sizes.retain(|handle, _| heap.contains(handle));
+        self.heap_bytes = self.sizes.values().sum();
+        self.weaks.retain(|_, handle| heap.contains(*handle));
+        self.frozen.retain(|handle| heap.contains(handle));
+        self.tags.retain(|handle, _| heap.contains(handle));
+    }
+
+    /// Insert a value on the heap without collecting garbage, keeping
+    /// track of its estimated size.
+    fn insert(&mut self, val: HVal) -> Handle<HVal> {
+        if let Some(hook) = &mut self.alloc_hook {
+            hook(&val);
+        }
+        let size = size::value_size(&val);
+        let handle = self.heap.insert_temp(val);
+        self.sizes.insert(handle, size);
+        self.heap_bytes += size;
+        handle
+    }
+
+    /// Allocate a value on the heap, returning undefined if the heap
+    /// limit is exceeded.
+    fn alloc(&mut self, val: HVal) -> Object {
+        self.try_alloc(val).unwrap_or(Object::Undef)
+    }
+
+    /// Allocate a value on the heap, collecting garbage if automatic
+    /// collection is enabled and the threshold is reached, or if the
+    /// heap limit is exceeded.  The new object survives the
+    /// collection.  Fails if the heap is still over the limit.
+    fn try_alloc(&mut self, val: HVal) -> Result<Object, GinkgoError> {
+        let handle = self.insert(val);
+        self.collect_after(handle)?;
+        Ok(Object::H(handle))
+    }
+
+    /// Collect garbage after inserting objects if automatic collection
+    /// is enabled and the threshold is reached, or if the heap limit is
+    /// exceeded.  The given object and what it refers to survive.
+    /// Fails if the heap is still over the limit.
+    fn collect_after(&mut self, handle: Handle<HVal>) -> Result<(), GinkgoError> {
+        if (self.auto_gc && self.heap.len() > self.next_gc) || self.over_limit() {
+            let rooted = self.heap.make_rooted(handle);
+            self.gc();
+            drop(rooted);
+            self.next_gc = self.gc_threshold.max(2 * self.heap.len());
+            if self.over_limit() {
+                return Err(GinkgoError::OutOfMemory);
+            }
+        }
+        Ok(())
+    }
+
+    fn over_limit(&self) -> bool {
+        self.heap_limit.is_some_and(|limit| self.heap_bytes > limit)
     }
 
     /// Return the heap size.
@@ -260,6 +560,17 @@ impl VM {
         self.heap.len()
     }
 
+    /// Set a function to be called with every value allocated on the
+    /// heap, before it is allocated.  Replaces any previous hook.
+    pub fn set_alloc_hook(&mut self, f: impl FnMut(&HVal) + 'static) {
+        self.alloc_hook = Some(Box::new(f));
+    }
+
+    /// Remove the allocation hook.
+    pub fn remove_alloc_hook(&mut self) {
+        self.alloc_hook = None;
+    }
+
     /// Create and return a new integer (fixnum) object.
     #[inline]
     pub fn int(&self, v: isize) -> Object {
@@ -272,11 +583,109 @@ impl VM {
         Object::S(SVal::Float(v))
     }
 
+    /// Create and return a new character object.
+    #[inline]
+    pub fn char(&self, c: char) -> Object {
+        Object::S(SVal::Char(c))
+    }
+
+    /// Return the interned symbol with the given name.
+    pub fn symbol(&mut self, name: &str) -> Object {
+        Object::S(SVal::Symbol(self.intern(name)))
+    }
+
+    /// Get the symbol ID for a name, adding it to the symbol table if
+    /// necessary.
+    fn intern(&mut self, name: &str) -> usize {
+        match self.symbol_ids.get(name) {
+            Some(&id) => id,
+            None => {
+                let id = self.symbols.len();
+                self.symbols.push(name.to_string());
+                self.symbol_ids.insert(name.to_string(), id);
+                id
+            }
+        }
+    }
+
+    /// Get the name of a symbol.
+    pub fn symbol_name(&self, obj: impl GObj) -> Option<&str> {
+        match obj.as_sval()? {
+            SVal::Symbol(id) => self.symbols.get(id).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// Return the boolean object `#t` or `#f`.  Predicates should
+    /// construct their results with this.
+    #[inline]
+    pub fn boolean(&self, b: bool) -> Object {
+        if b { Object::True } else { Object::False }
+    }
+
+    /// Check whether an object counts as true in a conditional.  Only
+    /// `#f` is false; everything else, including nil and zero, is true.
+    /// Nil is also false if enabled with `set_nil_is_false`.
+    #[inline]
+    pub fn is_truthy(&self, obj: impl GObj) -> bool {
+        match obj.as_sval() {
+            Some(SVal::Bool(false)) => false,
+            Some(SVal::Nil) => !self.nil_is_false,
+            _ => true,
+        }
+    }
+
+    /// Choose whether nil counts as false in conditionals, as in Common
+    /// Lisp.  By default it does not, as in Scheme.  This affects
+    /// `is_truthy`, `not` and `if`.
+    pub fn set_nil_is_false(&mut self, enabled: bool) {
+        self.nil_is_false = enabled;
+    }
+
+    /// Set the text printed for undefined objects, which is
+    /// `#undefined` by default.  This is useful for a REPL that wants
+    /// to print them as a comment, or not at all.
+    pub fn set_undefined_display(&mut self, s: &'static str) {
+        self.undefined_display = s;
+    }
+
+    /// Choose whether undefined objects inside lists, vectors and other
+    /// compound objects print as `_`, which makes partially initialized
+    /// structures easier to read.  An undefined object printed on its
+    /// own is unaffected.
+    pub fn set_hide_undefined(&mut self, enabled: bool) {
+        self.hide_undefined = enabled;
+    }
+
+    /// Set the radix exact integers are printed in, which is 10 by
+    /// default.  Radices 2, 8 and 16 are printed with the reader's
+    /// prefix, so that 255 in radix 16 is `#xff` and reads back as the
+    /// same number.  Rationals and floats are always printed in
+    /// decimal.  Returns `None`, leaving the radix unchanged, for other
+    /// radices.
+    pub fn set_print_radix(&mut self, radix: u32) -> Option<()> {
+        num::radix_prefix(radix)?;
+        self.print_radix = radix;
+        Some(())
+    }
+
+    /// Logical negation: return `#t` for false objects and `#f` for
+    /// everything else.
+    #[inline]
+    pub fn not(&self, obj: impl GObj) -> Object {
+        self.boolean(!self.is_truthy(obj))
+    }
+
     /// Create and return a new unrooted cons cell.
     #[inline]
     pub fn cons(&mut self, car: impl GObj, cdr: impl GObj) -> Object {
-        let handle = self.heap.insert_temp(HVal::Cons(car.unroot(), cdr.unroot()));
-        Object::H(handle)
+        self.alloc(HVal::Cons(car.unroot(), cdr.unroot()))
+    }
+
+    /// Create and return a new unrooted cons cell, or fail if the heap
+    /// limit is exceeded.
+    pub fn try_cons(&mut self, car: impl GObj, cdr: impl GObj) -> Result<Object, GinkgoError> {
+        self.try_alloc(HVal::Cons(car.unroot(), cdr.unroot()))
     }
 
     /// Get the car of a cons cell.
@@ -297,15 +706,55 @@ impl VM {
         }
     }
 
+    /// Set the car of a cons cell.
+    pub fn set_car(&mut self, obj: impl GObj, val: impl GObj) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
+        match self.direct_mut(obj) {
+            DObj::H(HVal::Cons(car, _)) => {
+                *car = val.unroot();
+                Ok(())
+            }
+            _ => Err(GinkgoError::TypeMismatch("cons")),
+        }
+    }
+
+    /// Set the cdr of a cons cell.
+    pub fn set_cdr(&mut self, obj: impl GObj, val: impl GObj) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
+        match self.direct_mut(obj) {
+            DObj::H(HVal::Cons(_, cdr)) => {
+                *cdr = val.unroot();
+                Ok(())
+            }
+            _ => Err(GinkgoError::TypeMismatch("cons")),
+        }
+    }
+
     /// Create and return a new unrooted vector with initial length,
     /// initialized with undefined objects.
     pub fn vec(&mut self, len: usize) -> Object {
         let vec = vec![Object::Undef; len];
-        let handle = self.heap.insert_temp(HVal::Vec(vec));
-        Object::H(handle)
+        self.alloc(HVal::Vec(Rc::new(vec)))
     }
 
-    /// Get the n'th element of a vector.
+    /// Create and return a new unrooted vector with initial length, or
+    /// fail if the heap limit is exceeded.
+    pub fn try_vec(&mut self, len: usize) -> Result<Object, GinkgoError> {
+        let vec = vec![Object::Undef; len];
+        self.try_alloc(HVal::Vec(Rc::new(vec)))
+    }
+
+    /// Create and return a new unrooted vector with the given elements.
+    pub fn vec_from_iter(&mut self, items: impl IntoIterator<Item = Object>) -> Object {
+        let vec = items.into_iter().collect();
+        self.alloc(HVal::Vec(Rc::new(vec)))
+    }
+
+    /// Get the n'th element of a vector.  Returns `None` both for
+    /// non-vectors and for indices out of range; see `vec_ref` to tell
+    /// them apart.
     pub fn vec_get(&self, obj: impl GObj, index: usize) -> Option<Object> {
         match self.direct(obj) {
             DObj::H(HVal::Vec(vec)) => vec.get(index).copied(),
@@ -313,36 +762,115 @@ impl VM {
         }
     }
 
-    /// Set the n'th element of a vector.
-    pub fn vec_set(&mut self, obj: impl GObj, index: usize, val: impl GObj) -> Result<(), ()> {
+    /// Get the n'th element of a vector, failing with
+    /// `GinkgoError::TypeMismatch` for non-vectors and
+    /// `GinkgoError::IndexOutOfBounds` for indices out of range.
+    pub fn vec_ref(&self, obj: impl GObj, index: usize) -> Result<Object, GinkgoError> {
+        match self.direct(obj) {
+            DObj::H(HVal::Vec(vec)) => vec.get(index).copied()
+                .ok_or(GinkgoError::IndexOutOfBounds { index, len: vec.len(), kind: "vector" }),
+            _ => Err(GinkgoError::TypeMismatch("vector")),
+        }
+    }
+
+    /// Set the n'th element of a vector.  If the vector shares its
+    /// elements with others, it gets its own copy first.
+    pub fn vec_set(&mut self, obj: impl GObj, index: usize, val: impl GObj) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
         match self.direct_mut(obj) {
             DObj::H(HVal::Vec(vec)) => {
                 if index < vec.len() {
-                    vec[index] = val.unroot();
+                    Rc::make_mut(vec)[index] = val.unroot();
                     Ok(())
                 } else {
-                    Err(())
+                    Err(GinkgoError::IndexOutOfBounds { index, len: vec.len(), kind: "vector" })
                 }
             },
-            _ => Err(()),
+            _ => Err(GinkgoError::TypeMismatch("vector")),
         }
     }
 
     /// Create and return a new string.
     pub fn string(&mut self, s: String) -> Object {
-        let handle = self.heap.insert_temp(HVal::String(s));
-        Object::H(handle)
+        self.alloc(HVal::String(s))
+    }
+
+    /// Create and return a new string, or fail if the heap limit is
+    /// exceeded.
+    pub fn try_string(&mut self, s: String) -> Result<Object, GinkgoError> {
+        self.try_alloc(HVal::String(s))
+    }
+
+    /// Create and return a new procedure object wrapping a Rust
+    /// function.
+    pub fn native(&mut self, f: NativeFn) -> Object {
+        self.alloc(HVal::Native(f))
     }
 
     /// Create a combined short-lived VM-object.
     #[inline]
-    pub fn wrap(&self, obj: impl GObj) -> WrappedObject {
-        WrappedObject { vm: self, object: obj.unroot() }
+    pub fn wrap(&self, obj: impl GObj) -> WrappedObject<'_> {
+        WrappedObject { vm: self, object: obj.unroot(), max_string_len: None }
+    }
+
+    /// Print an object for debugging, showing at most
+    /// `max_string_len` characters of each string, followed by the
+    /// number of characters left out, as in `"abc...<997 more chars>"`.
+    /// The output cannot always be read back.  Displaying a wrapped
+    /// object never truncates.
+    pub fn write_truncated(&self, out: &mut impl fmt::Write, obj: impl GObj, max_string_len: usize) -> fmt::Result {
+        let wrapped = WrappedObject { vm: self, object: obj.unroot(), max_string_len: Some(max_string_len) };
+        write!(out, "{}", wrapped)
+    }
+
+    /// Describe an object for diagnostics, with its type, its address
+    /// if it lives on the heap, and its printed form, e.g.
+    /// `#<cons @0x5581d0a2c0 (1 2 3)>`.  Objects that have been
+    /// collected are shown as `#<dead @...>`.
+    pub fn debug_string(&self, obj: impl GObj) -> String {
+        let obj = obj.unroot();
+        let name = match self.direct(obj) {
+            DObj::D(ptr) => return format!("#<dead @{:p}>", ptr),
+            DObj::S(SVal::Undefined) => "undefined",
+            DObj::S(SVal::Nil) => "nil",
+            DObj::S(SVal::Bool(_)) => "bool",
+            DObj::S(SVal::Int(_)) => "int",
+            DObj::S(SVal::Float(_)) => "float",
+            DObj::S(SVal::Symbol(_)) => "symbol",
+            DObj::S(SVal::Char(_)) => "char",
+            DObj::H(HVal::Cons(..)) => "cons",
+            DObj::H(HVal::Vec(_)) => "vector",
+            DObj::H(HVal::String(_)) => "string",
+            DObj::H(HVal::Rational(..)) => "rational",
+            #[cfg(feature = "bignum")]
+            DObj::H(HVal::BigInt(_)) => "bigint",
+            DObj::H(HVal::Native(_)) => "native",
+            DObj::H(HVal::Foreign(_)) => "foreign",
+            DObj::H(HVal::Env { .. }) => "environment",
+            DObj::H(HVal::Closure { .. }) => "procedure",
+            DObj::H(HVal::Error { .. }) => "error",
+            DObj::H(HVal::Values(_)) => "values",
+            DObj::H(HVal::Promise { .. }) => "promise",
+        };
+        match self.direct(obj) {
+            DObj::H(val) => format!("#<{} @{:p} {}>", name, val, self.wrap(obj)),
+            _ => format!("#<{} {}>", name, self.wrap(obj)),
+        }
+    }
+
+    /// Lend the heap value of an object to a function, and return its
+    /// result.  Returns `None` for stack values and dead objects.
+    pub fn with_heap_value<R>(&self, obj: impl GObj, f: impl FnOnce(&HVal) -> R) -> Option<R> {
+        match self.direct(obj) {
+            DObj::H(val) => Some(f(val)),
+            _ => None,
+        }
     }
 
     /// Destructure a Ginkgo object into an object that lives fully on the stack.
     #[inline]
-    fn direct(&self, obj: impl GObj) -> DirectObject {
+    fn direct(&self, obj: impl GObj) -> DirectObject<'_> {
         match obj.unroot() {
             Object::S(v) => DObj::S(v),
             Object::H(handle) => match self.heap.get(handle) {
@@ -354,7 +882,7 @@ impl VM {
 
     /// Destructure a Ginkgo object into a mutable object that lives fully on the stack.
     #[inline]
-    fn direct_mut(&mut self, obj: impl GObj) -> DirectObjectMut {
+    fn direct_mut(&mut self, obj: impl GObj) -> DirectObjectMut<'_> {
         match obj.unroot() {
             Object::S(v) => DObj::S(v),
             Object::H(handle) => match self.heap.get_mut(handle) {
@@ -0,0 +1,375 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use broom::Handle;
+
+use crate::{DObj, GinkgoError, GObj, HVal, Keyed, Object, SVal, VM};
+
+
+impl VM {
+    /// Create and return a new proper list of the given elements.
+    /// Garbage collection and the heap limit are checked once for the
+    /// whole list, so the elements survive while it is built.  Returns
+    /// undefined if the heap limit is exceeded.
+    pub fn list(&mut self, items: &[Object]) -> Object {
+        self.try_prepend(items, Object::Nil).unwrap_or(Object::Undef)
+    }
+
+    /// Create a list of the given elements in front of a tail, checking
+    /// garbage collection and the heap limit once at the end.  Fails if
+    /// the heap is over the limit.
+    pub(crate) fn try_prepend(&mut self, items: &[Object], tail: Object) -> Result<Object, GinkgoError> {
+        if items.is_empty() {
+            return Ok(tail);
+        }
+        self.sizes.reserve(items.len());
+        let mut list = tail;
+        for item in items.iter().rev() {
+            list = Object::H(self.insert(HVal::Cons(*item, list)));
+        }
+        if let Object::H(handle) = list {
+            self.collect_after(handle)?;
+        }
+        Ok(list)
+    }
+
+    /// Append two lists.  The first list is copied and the second is
+    /// shared.  Returns `None` if the first list is improper.
+    pub fn append(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let items = self.list_to_vec(a)?;
+        Some(self.try_prepend(&items, b.unroot()).unwrap_or(Object::Undef))
+    }
+
+    /// Concatenate a list of lists into a single list, one level deep.
+    /// All but the last sublist are copied.  Returns `None` if the
+    /// outer list or any of its elements is not a proper list.
+    pub fn flatten_once(&mut self, obj: impl GObj) -> Option<Object> {
+        let lists = self.list_to_vec(obj)?;
+        let (last, init) = match lists.split_last() {
+            Some((last, init)) => (*last, init),
+            None => return Some(Object::Nil),
+        };
+        self.list_to_vec(last)?;
+
+        let mut items = Vec::new();
+        for list in init {
+            items.extend(self.list_to_vec(*list)?);
+        }
+        Some(self.try_prepend(&items, last).unwrap_or(Object::Undef))
+    }
+
+    /// Return a new list of the first `n` elements of a list, or of all
+    /// the elements if the list is shorter.  Returns `None` if an
+    /// improper tail is reached before `n` elements.
+    pub fn take(&mut self, obj: impl GObj, n: usize) -> Option<Object> {
+        let mut items = Vec::new();
+        let mut tail = obj.unroot();
+        while items.len() < n {
+            match self.direct(tail) {
+                DObj::S(SVal::Nil) => break,
+                DObj::H(&HVal::Cons(car, cdr)) => {
+                    items.push(car);
+                    tail = cdr;
+                }
+                _ => return None,
+            }
+        }
+        Some(self.list(&items))
+    }
+
+    /// Return the sublist after skipping the first `n` elements of a
+    /// list, sharing structure with it, or nil if the list is shorter.
+    /// Returns `None` if an improper tail is reached before `n`
+    /// elements.
+    pub fn drop(&self, obj: impl GObj, n: usize) -> Option<Object> {
+        let mut tail = obj.unroot();
+        for _ in 0..n {
+            match self.direct(tail) {
+                DObj::S(SVal::Nil) => break,
+                DObj::H(&HVal::Cons(_, cdr)) => tail = cdr,
+                _ => return None,
+            }
+        }
+        Some(tail)
+    }
+
+    /// Return the sublist after skipping exactly `k` cons cells of a
+    /// list, sharing structure with it, like Scheme's `list-tail`.
+    /// Unlike `drop`, returns `None` if there are fewer than `k` cells.
+    /// The remaining tail need not be a proper list.
+    pub fn list_tail(&self, obj: impl GObj, k: usize) -> Option<Object> {
+        let mut tail = obj.unroot();
+        for _ in 0..k {
+            tail = self.cdr(tail)?;
+        }
+        Some(tail)
+    }
+
+    /// Return a new list with duplicate elements removed, keeping the
+    /// first occurrence of each.  Elements are compared with `equal`.
+    /// Returns `None` for improper lists.
+    pub fn dedup_list(&mut self, obj: impl GObj) -> Option<Object> {
+        let items = self.list_to_vec(obj)?;
+        let mut seen: HashMap<u64, Vec<Object>> = HashMap::new();
+        let mut unique = Vec::new();
+
+        for item in items {
+            let bucket = seen.entry(self.hash(item)).or_default();
+            if !bucket.iter().any(|other| self.equal(*other, item)) {
+                bucket.push(item);
+                unique.push(item);
+            }
+        }
+
+        Some(self.list(&unique))
+    }
+
+    /// Return a new list of the elements in either of two lists, in the
+    /// order of the first followed by the second.  Lists are treated as
+    /// sets: elements are compared with `equal`, and the result has no
+    /// duplicates.  Returns `None` for improper lists.
+    pub fn list_union(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let mut items = self.list_to_vec(a)?;
+        items.extend(self.list_to_vec(b)?);
+        let items = self.unique_where(&items, |_| true);
+        Some(self.list(&items))
+    }
+
+    /// Return a new list of the elements of the first list that are
+    /// also in the second, in their order in the first.  Lists are
+    /// treated as sets, as by `list_union`.
+    pub fn list_intersection(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let (a, b) = (self.list_to_vec(a)?, self.list_to_vec(b)?);
+        let items = {
+            let other: HashSet<Keyed> = b.iter().map(|item| self.keyed(*item)).collect();
+            self.unique_where(&a, |key| other.contains(key))
+        };
+        Some(self.list(&items))
+    }
+
+    /// Return a new list of the elements of the first list that are
+    /// not in the second, in their order in the first.  Lists are
+    /// treated as sets, as by `list_union`.
+    pub fn list_difference(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let (a, b) = (self.list_to_vec(a)?, self.list_to_vec(b)?);
+        let items = {
+            let other: HashSet<Keyed> = b.iter().map(|item| self.keyed(*item)).collect();
+            self.unique_where(&a, |key| !other.contains(key))
+        };
+        Some(self.list(&items))
+    }
+
+    /// Select the first occurrence of each distinct element for which
+    /// a predicate holds.
+    fn unique_where(&self, items: &[Object], keep: impl Fn(&Keyed) -> bool) -> Vec<Object> {
+        let mut seen = HashSet::new();
+        items.iter()
+            .copied()
+            .filter(|item| {
+                let key = self.keyed(*item);
+                keep(&key) && seen.insert(key)
+            })
+            .collect()
+    }
+
+    /// Call a function on each element of a list in order, for its side
+    /// effects, like Scheme's `for-each`.  Returns `None` without
+    /// calling the function for improper and cyclic lists.  The
+    /// elements are collected before the first call and kept alive
+    /// until the last, so the function visits all of them even if it
+    /// modifies the list.
+    pub fn for_each_list(&mut self, obj: impl GObj, mut f: impl FnMut(&mut VM, Object)) -> Option<()> {
+        let items = self.list_to_vec(obj)?;
+        let items: Vec<_> = items.into_iter().map(|item| item.root(self)).collect();
+        for item in &items {
+            f(self, item.unroot());
+        }
+        Some(())
+    }
+
+    /// Return a new list with the elements sorted according to a
+    /// comparator.  The sort is stable.  Returns `None` for improper
+    /// lists.
+    pub fn sort_list(
+        &mut self,
+        obj: impl GObj,
+        mut cmp: impl FnMut(&mut VM, Object, Object) -> Ordering,
+    ) -> Option<Object> {
+        // The comparator may collect garbage, so the elements are kept
+        // alive through the list
+        let obj = obj.unroot();
+        let _rooted = obj.root(self);
+        let mut items = self.list_to_vec(obj)?;
+        items.sort_by(|a, b| cmp(self, *a, *b));
+        Some(self.list(&items))
+    }
+
+    /// Return a new list with the elements sorted in ascending numeric
+    /// order.  Returns `None` for improper lists and if any element is
+    /// not a number or is NaN.
+    pub fn sort_list_numeric(&mut self, obj: impl GObj) -> Option<Object> {
+        let mut items = self.list_to_vec(obj)?;
+        if !items.iter().all(|item| self.num_cmp(*item, *item).is_some()) {
+            return None;
+        }
+        items.sort_by(|a, b| self.num_cmp(*a, *b).unwrap());
+        Some(self.list(&items))
+    }
+
+    /// Split a possibly improper list into its elements and its final
+    /// cdr, which is nil for a proper list.  A non-list gives no
+    /// elements and itself as the tail.  For a cyclic list, collection
+    /// stops once the cycle is detected, and the tail is the cons cell
+    /// where it stopped.
+    pub fn list_parts(&self, obj: impl GObj) -> (Vec<Object>, Object) {
+        let mut iter = self.iter_list(obj);
+        let items = iter.by_ref().collect();
+        (items, iter.tail)
+    }
+
+    /// Iterate over the elements of a list.  Iteration stops at the
+    /// first cdr that is not a cons cell, so an improper tail is not
+    /// included, and once a cycle is detected in a cyclic list.  The
+    /// iterator borrows the VM, so it cannot be mutated during
+    /// iteration.
+    ///
+    /// Together with `length`, `nth` and `list_parts`, this is the
+    /// supported way to traverse code as data.
+    pub fn iter_list(&self, obj: impl GObj) -> ListIter<'_> {
+        let obj = obj.unroot();
+        ListIter { vm: self, tail: obj, slow: obj, count: 0 }
+    }
+
+    /// Get the number of elements in a proper list.  Returns `None`
+    /// for improper and cyclic lists.
+    pub fn length(&self, obj: impl GObj) -> Option<usize> {
+        let mut iter = self.iter_list(obj);
+        let len = iter.by_ref().count();
+        match iter.tail {
+            Object::Nil => Some(len),
+            _ => None,
+        }
+    }
+
+    /// Get the n'th element of a list, counting from zero.  Returns
+    /// `None` if the list is shorter, or if an improper tail is reached
+    /// first.
+    pub fn nth(&self, obj: impl GObj, n: usize) -> Option<Object> {
+        self.car(self.drop(obj, n)?)
+    }
+
+    /// Find the first sublist of a list whose car is `eqv` to an
+    /// object, like Scheme's `memv`.  Returns `None` if there is no
+    /// such element.
+    pub fn memv(&self, obj: impl GObj, list: impl GObj) -> Option<Object> {
+        let obj = obj.unroot();
+        self.list_cells(list).into_iter()
+            .map(Object::H)
+            .find(|cell| self.eqv(self.car(*cell).unwrap(), obj))
+    }
+
+    /// Find the first pair in an association list whose car is `eqv` to
+    /// a key, like Scheme's `assv`.  Elements that are not pairs are
+    /// skipped.  Returns `None` if there is no such pair.
+    pub fn assv(&self, key: impl GObj, alist: impl GObj) -> Option<Object> {
+        let key = key.unroot();
+        self.iter_list(alist).find(|entry| self.car(*entry).is_some_and(|k| self.eqv(k, key)))
+    }
+
+    /// Return a new association list with the binding for a key set to
+    /// a value, leaving the original unchanged.  Keys are compared with
+    /// `equal`.  If the key is bound, the pairs before its binding are
+    /// copied and the rest of the list is shared; otherwise, a new
+    /// binding is added in front of the whole list.
+    pub fn alist_set(&mut self, alist: impl GObj, key: impl GObj, value: impl GObj) -> Object {
+        let (alist, key, value) = (alist.unroot(), key.unroot(), value.unroot());
+        let _rooted = alist.root(self);
+        let (entries, _) = self.list_parts(alist);
+        let found = entries.iter()
+            .position(|entry| self.car(*entry).is_some_and(|k| self.equal(k, key)));
+        let index = match found {
+            Some(index) => index,
+            None => {
+                let binding = self.cons(key, value);
+                return self.cons(binding, alist);
+            }
+        };
+
+        // Keep the key object of the old binding
+        let old_key = self.car(entries[index]).unwrap();
+        let binding = self.cons(old_key, value);
+        let rest = self.list_tail(alist, index + 1).unwrap();
+        let tail = self.cons(binding, rest);
+        self.try_prepend(&entries[..index], tail).unwrap_or(Object::Undef)
+    }
+
+    /// Check whether two lists share structure, that is, whether they
+    /// reach a common cons cell.  Cyclic lists are walked once around.
+    pub fn shares_tail(&self, a: impl GObj, b: impl GObj) -> bool {
+        let cells: HashSet<_> = self.list_cells(a).into_iter().collect();
+        self.list_cells(b).iter().any(|cell| cells.contains(cell))
+    }
+
+    /// Collect the cons cells making up a list, stopping at the first
+    /// non-cons cdr or once a cycle is detected.
+    fn list_cells(&self, obj: impl GObj) -> Vec<Handle<HVal>> {
+        let mut cells = Vec::new();
+        let mut tail = obj.unroot();
+        let mut slow = tail;
+
+        while let (Object::H(handle), DObj::H(&HVal::Cons(_, cdr))) = (tail, self.direct(tail)) {
+            cells.push(handle);
+            tail = cdr;
+
+            // Advance a second pointer at half speed to detect cycles
+            if cells.len() % 2 == 0 {
+                slow = self.cdr(slow).unwrap();
+                if slow == tail {
+                    break;
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Collect the elements of a proper list.  Returns `None` for
+    /// improper or cyclic lists.
+    pub(crate) fn list_to_vec(&self, obj: impl GObj) -> Option<Vec<Object>> {
+        match self.list_parts(obj) {
+            (items, Object::Nil) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+
+/// Iterator over the elements of a list, created by `VM::iter_list`.
+pub struct ListIter<'a> {
+    vm: &'a VM,
+    tail: Object,
+    slow: Object,
+    count: usize,
+}
+
+impl Iterator for ListIter<'_> {
+    type Item = Object;
+
+    fn next(&mut self) -> Option<Object> {
+        let (car, cdr) = match self.vm.direct(self.tail) {
+            DObj::H(&HVal::Cons(car, cdr)) => (car, cdr),
+            _ => return None,
+        };
+        if self.count > 0 && self.count.is_multiple_of(2) && self.slow == self.tail {
+            return None;
+        }
+        self.tail = cdr;
+        self.count += 1;
+
+        // Advance a second pointer at half speed to detect cycles
+        if self.count.is_multiple_of(2) {
+            self.slow = self.vm.cdr(self.slow).unwrap();
+        }
+        Some(car)
+    }
+}
@@ -0,0 +1,534 @@
+use std::char::from_digit;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+#[cfg(feature = "bignum")]
+use num_bigint::{BigInt, Sign};
+#[cfg(feature = "bignum")]
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{DObj, GObj, HVal, Object, SVal, VM};
+use crate::read;
+
+
+/// Numeric view of an object, used internally for arithmetic.
+/// Rationals are always in lowest terms with a positive denominator
+/// greater than one, and bignums are always out of fixnum range.
+#[derive(Clone, Debug, PartialEq)]
+enum Num {
+    Int(isize),
+    Rat(isize, isize),
+    Float(f64),
+    #[cfg(feature = "bignum")]
+    Big(BigInt),
+}
+
+/// Arithmetic operation selector.
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Num {
+    fn to_f64(&self) -> f64 {
+        match self {
+            Num::Int(v) => *v as f64,
+            Num::Rat(n, d) => *n as f64 / *d as f64,
+            Num::Float(v) => *v,
+            #[cfg(feature = "bignum")]
+            Num::Big(v) => v.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    fn arith(self, other: Num, op: Op) -> Option<Num> {
+        match (self, other) {
+            (Num::Int(a), Num::Int(b)) if op != Op::Div => {
+                let r = match op {
+                    Op::Add => a.checked_add(b),
+                    Op::Sub => a.checked_sub(b),
+                    _ => a.checked_mul(b),
+                };
+                match r {
+                    Some(v) => Some(Num::Int(v)),
+                    None => Num::Int(a).big_arith(Num::Int(b), op),
+                }
+            }
+            (a @ Num::Float(_), b) | (a, b @ Num::Float(_)) => {
+                let (a, b) = (a.to_f64(), b.to_f64());
+                Some(Num::Float(match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                }))
+            }
+            #[cfg(feature = "bignum")]
+            (a @ Num::Big(_), b) | (a, b @ Num::Big(_)) => a.big_arith(b, op),
+            (a, b) => {
+                let ((an, ad), (bn, bd)) = (a.parts()?, b.parts()?);
+                let r = match op {
+                    Op::Add => ratio(an * bd + bn * ad, ad * bd),
+                    Op::Sub => ratio(an * bd - bn * ad, ad * bd),
+                    Op::Mul => ratio(an * bn, ad * bd),
+                    Op::Div => ratio(an * bd, ad * bn),
+                };
+                r.or_else(|| a.big_arith(b, op))
+            }
+        }
+    }
+
+    fn compare(&self, other: &Num) -> Option<Ordering> {
+        match (self, other) {
+            (Num::Float(_), _) | (_, Num::Float(_)) => self.to_f64().partial_cmp(&other.to_f64()),
+            #[cfg(feature = "bignum")]
+            (Num::Big(_), _) | (_, Num::Big(_)) => match (self.to_big(), other.to_big()) {
+                (Some(a), Some(b)) => Some(a.cmp(&b)),
+                _ => self.to_f64().partial_cmp(&other.to_f64()),
+            },
+            _ => {
+                let ((an, ad), (bn, bd)) = (self.parts()?, other.parts()?);
+                Some((an * bd).cmp(&(bn * ad)))
+            }
+        }
+    }
+
+    /// Numerator and denominator of an exact fixnum-sized number.
+    fn parts(&self) -> Option<(i128, i128)> {
+        match *self {
+            Num::Int(v) => Some((v as i128, 1)),
+            Num::Rat(n, d) => Some((n as i128, d as i128)),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "bignum")]
+    fn to_big(&self) -> Option<BigInt> {
+        match self {
+            Num::Int(v) => Some(BigInt::from(*v)),
+            Num::Big(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    /// Numerator and denominator of an exact number with arbitrary
+    /// precision.
+    #[cfg(feature = "bignum")]
+    fn big_parts(&self) -> Option<(BigInt, BigInt)> {
+        match self {
+            Num::Rat(n, d) => Some((BigInt::from(*n), BigInt::from(*d))),
+            _ => Some((self.to_big()?, BigInt::one())),
+        }
+    }
+
+    #[cfg(feature = "bignum")]
+    fn from_big(v: BigInt) -> Num {
+        match isize::try_from(&v) {
+            Ok(v) => Num::Int(v),
+            Err(_) => Num::Big(v),
+        }
+    }
+
+    /// Perform an operation on exact numbers with arbitrary precision.
+    /// Whole results are integers, promoted to bignums as needed, but
+    /// other results are only supported as rationals with fixnum parts,
+    /// and return `None` otherwise.
+    #[cfg(feature = "bignum")]
+    fn big_arith(self, other: Num, op: Op) -> Option<Num> {
+        let ((an, ad), (bn, bd)) = (self.big_parts()?, other.big_parts()?);
+        match op {
+            Op::Add => big_ratio(an * &bd + bn * &ad, ad * bd),
+            Op::Sub => big_ratio(an * &bd - bn * &ad, ad * bd),
+            Op::Mul => big_ratio(an * bn, ad * bd),
+            Op::Div => big_ratio(an * bd, ad * bn),
+        }
+    }
+
+    #[cfg(not(feature = "bignum"))]
+    fn big_arith(self, _: Num, _: Op) -> Option<Num> {
+        None
+    }
+
+    /// Raise an integer to a non-negative power with arbitrary
+    /// precision.
+    #[cfg(feature = "bignum")]
+    fn big_pow(self, exp: isize) -> Option<Num> {
+        Some(Num::from_big(self.to_big()?.pow(u32::try_from(exp).ok()?)))
+    }
+
+    #[cfg(not(feature = "bignum"))]
+    fn big_pow(self, _: isize) -> Option<Num> {
+        None
+    }
+}
+
+
+impl VM {
+    /// Create and return a new exact rational number in lowest terms.
+    /// Whole-valued rationals are returned as integers.  Returns `None`
+    /// if the denominator is zero.
+    pub fn rational(&mut self, num: isize, den: isize) -> Option<Object> {
+        let r = ratio(num as i128, den as i128)?;
+        Some(self.make_num(r))
+    }
+
+    /// Add two numbers.  Returns `None` for non-numbers or on overflow.
+    /// With the `bignum` feature, integer overflow promotes to a bignum
+    /// instead, and this applies to all arithmetic.  Rationals are
+    /// still limited to fixnum numerators and denominators, so adding
+    /// a bignum and a rational, or dividing a bignum by an integer that
+    /// does not divide it, returns `None`.
+    pub fn add(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let r = self.num(a)?.arith(self.num(b)?, Op::Add)?;
+        Some(self.make_num(r))
+    }
+
+    /// Subtract two numbers.  Returns `None` for non-numbers or on
+    /// overflow.
+    pub fn sub(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let r = self.num(a)?.arith(self.num(b)?, Op::Sub)?;
+        Some(self.make_num(r))
+    }
+
+    /// Multiply two numbers.  Returns `None` for non-numbers or on
+    /// overflow.
+    pub fn mul(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let r = self.num(a)?.arith(self.num(b)?, Op::Mul)?;
+        Some(self.make_num(r))
+    }
+
+    /// Divide two numbers.  Dividing exact numbers produces an exact
+    /// result, which is rational if necessary.  Returns `None` for
+    /// non-numbers, on overflow, or on exact division by zero.
+    pub fn div(&mut self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        let r = self.num(a)?.arith(self.num(b)?, Op::Div)?;
+        Some(self.make_num(r))
+    }
+
+    /// Raise a number to a power.  An exact base with a non-negative
+    /// integer exponent stays exact, returning `None` on overflow.
+    /// Negative integer exponents and float operands produce a float.
+    pub fn expt(&mut self, base: impl GObj, exp: impl GObj) -> Option<Object> {
+        let r = match (self.num(base)?, self.num(exp)?) {
+            (Num::Int(b), Num::Int(e)) if e >= 0 => match int_pow(b, e) {
+                Some(v) => Num::Int(v),
+                None => Num::Int(b).big_pow(e)?,
+            },
+            #[cfg(feature = "bignum")]
+            (b @ Num::Big(_), Num::Int(e)) if e >= 0 => b.big_pow(e)?,
+            (Num::Rat(n, d), Num::Int(e)) if e >= 0 => ratio(int_pow(n, e)? as i128, int_pow(d, e)? as i128)?,
+            (b, e) => Num::Float(b.to_f64().powf(e.to_f64())),
+        };
+        Some(self.make_num(r))
+    }
+
+    /// Round a number towards negative infinity.  Floats stay floats,
+    /// rationals become integers, and integers are returned unchanged.
+    pub fn floor(&self, obj: impl GObj) -> Option<Object> {
+        self.round_with(obj, f64::floor, |n, d| n.div_euclid(d))
+    }
+
+    /// Round a number towards positive infinity.  Floats stay floats,
+    /// rationals become integers, and integers are returned unchanged.
+    pub fn ceiling(&self, obj: impl GObj) -> Option<Object> {
+        self.round_with(obj, f64::ceil, |n, d| n.div_euclid(d) + 1)
+    }
+
+    /// Round a number to the nearest integer, with ties going to the
+    /// even integer.  Floats stay floats, rationals become integers,
+    /// and integers are returned unchanged.
+    pub fn round(&self, obj: impl GObj) -> Option<Object> {
+        self.round_with(obj, f64::round_ties_even, |n, d| {
+            let (q, r) = (n.div_euclid(d), n.rem_euclid(d));
+            if r < d - r || (r == d - r && q % 2 == 0) { q } else { q + 1 }
+        })
+    }
+
+    /// Round a number towards zero.  Floats stay floats, rationals
+    /// become integers, and integers are returned unchanged.
+    pub fn truncate(&self, obj: impl GObj) -> Option<Object> {
+        self.round_with(obj, f64::trunc, |n, d| n / d)
+    }
+
+    /// Convert an exact number to the equivalent float.  Floats are
+    /// returned unchanged.
+    pub fn exact_to_inexact(&self, obj: impl GObj) -> Option<Object> {
+        self.num(obj).map(|n| self.float(n.to_f64()))
+    }
+
+    /// Convert a float with an integral value to the equivalent
+    /// integer.  Returns `None` for floats that are not integral or
+    /// out of range, and for non-numbers.  Exact numbers, including
+    /// rationals and bignums, are returned unchanged.
+    pub fn inexact_to_exact(&self, obj: impl GObj) -> Option<Object> {
+        let obj = obj.unroot();
+        match self.num(obj)? {
+            Num::Float(v) => float_to_int(v).map(|v| self.int(v)),
+            _ => Some(obj),
+        }
+    }
+
+    /// Bitwise and of two fixnums, in two's complement.  Returns `None`
+    /// for other objects, including bignums.
+    pub fn bit_and(&self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        Some(self.int(a.as_int()? & b.as_int()?))
+    }
+
+    /// Bitwise inclusive or of two fixnums, in two's complement.
+    /// Returns `None` for other objects, including bignums.
+    pub fn bit_or(&self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        Some(self.int(a.as_int()? | b.as_int()?))
+    }
+
+    /// Bitwise exclusive or of two fixnums, in two's complement.
+    /// Returns `None` for other objects, including bignums.
+    pub fn bit_xor(&self, a: impl GObj, b: impl GObj) -> Option<Object> {
+        Some(self.int(a.as_int()? ^ b.as_int()?))
+    }
+
+    /// Bitwise complement of a fixnum in two's complement, so that the
+    /// result is `-1 - n`.  Returns `None` for other objects.
+    pub fn bit_not(&self, obj: impl GObj) -> Option<Object> {
+        Some(self.int(!obj.as_int()?))
+    }
+
+    /// Shift a fixnum left by a number of bits, multiplying it by a
+    /// power of two.  Returns `None` if the amount is negative, or if
+    /// the result does not fit in a fixnum.
+    pub fn shift_left(&self, obj: impl GObj, amount: impl GObj) -> Option<Object> {
+        let (v, n) = (obj.as_int()?, usize::try_from(amount.as_int()?).ok()?);
+        if v == 0 {
+            return Some(self.int(0));
+        }
+        let n = u32::try_from(n).ok()?;
+        let r = v.checked_shl(n)?;
+        if r >> n != v {
+            return None;
+        }
+        Some(self.int(r))
+    }
+
+    /// Shift a fixnum right by a number of bits, dividing it by a power
+    /// of two and rounding towards negative infinity.  Amounts larger
+    /// than the width of a fixnum give 0 or -1, by the sign.  Returns
+    /// `None` if the amount is negative.
+    pub fn shift_right(&self, obj: impl GObj, amount: impl GObj) -> Option<Object> {
+        let (v, n) = (obj.as_int()?, usize::try_from(amount.as_int()?).ok()?);
+        Some(self.int(v >> n.min(isize::BITS as usize - 1)))
+    }
+
+    /// Check whether two numbers have the same value, like Scheme's
+    /// `=`, so that `1` equals `1.0`.  NaN is not equal to anything.
+    /// Returns `None` if either argument is not a number.
+    pub fn num_equal(&self, a: impl GObj, b: impl GObj) -> Option<bool> {
+        let (a, b) = (self.num(a)?, self.num(b)?);
+        Some(a.compare(&b) == Some(Ordering::Equal))
+    }
+
+    /// Compare two numbers by value.  Returns `None` for non-numbers
+    /// and NaN.
+    pub(crate) fn num_cmp(&self, a: impl GObj, b: impl GObj) -> Option<Ordering> {
+        self.num(a)?.compare(&self.num(b)?)
+    }
+
+    /// Compute the integer square root of a non-negative exact integer,
+    /// like Scheme's `exact-integer-sqrt`.  Returns the largest integer
+    /// whose square is at most the number, and the difference between
+    /// the number and that square.  Returns `None` for negative numbers
+    /// and non-integers.
+    pub fn exact_integer_sqrt(&mut self, obj: impl GObj) -> Option<(Object, Object)> {
+        let (root, rem) = match self.num(obj)? {
+            Num::Int(v) if v >= 0 => {
+                let root = (v as usize).isqrt() as isize;
+                (Num::Int(root), Num::Int(v - root * root))
+            }
+            #[cfg(feature = "bignum")]
+            Num::Big(v) if v.sign() != Sign::Minus => {
+                let root = v.sqrt();
+                let rem = &v - &root * &root;
+                (Num::from_big(root), Num::from_big(rem))
+            }
+            _ => return None,
+        };
+        let root = self.make_num(root);
+        let _rooted = root.root(self);
+        Some((root, self.make_num(rem)))
+    }
+
+    /// Format an exact integer as a string in the given radix, using
+    /// lowercase letters for digits above 9, so that 255 in radix 16
+    /// is `ff`.  Returns `None` for non-integers and for radices
+    /// outside 2 to 36.  This takes `&mut self` since it allocates the
+    /// string; see `set_print_radix` to print integers in a radix.
+    pub fn int_to_string_radix(&mut self, obj: impl GObj, radix: u32) -> Option<Object> {
+        if !(2..=36).contains(&radix) {
+            return None;
+        }
+        let s = match self.num(obj)? {
+            Num::Int(v) => int_to_radix(v, radix),
+            #[cfg(feature = "bignum")]
+            Num::Big(v) => v.to_str_radix(radix),
+            _ => return None,
+        };
+        Some(self.string(s))
+    }
+
+    /// Parse a number using the syntax of the reader, like Scheme's
+    /// `string->number`.  This includes rationals such as `3/4` and
+    /// radix prefixes such as `#xff`.  Returns `None` if the whole
+    /// string is not a number, or is one the reader rejects, such as
+    /// `1/0`.
+    pub fn string_to_number(&mut self, s: &str) -> Option<Object> {
+        read::number(self, s)?.ok()
+    }
+
+    fn round_with(
+        &self,
+        obj: impl GObj,
+        f: fn(f64) -> f64,
+        r: fn(isize, isize) -> isize,
+    ) -> Option<Object> {
+        let obj = obj.unroot();
+        match self.num(obj)? {
+            Num::Rat(n, d) => Some(self.int(r(n, d))),
+            Num::Float(v) => Some(self.float(f(v))),
+            _ => Some(obj),
+        }
+    }
+
+    /// Get the numeric view of an object, if it is a number.
+    fn num(&self, obj: impl GObj) -> Option<Num> {
+        match self.direct(obj) {
+            DObj::S(SVal::Int(v)) => Some(Num::Int(v)),
+            DObj::S(SVal::Float(v)) => Some(Num::Float(v)),
+            DObj::H(&HVal::Rational(n, d)) => Some(Num::Rat(n, d)),
+            #[cfg(feature = "bignum")]
+            DObj::H(HVal::BigInt(v)) => Some(Num::Big(v.clone())),
+            _ => None,
+        }
+    }
+
+    /// Create an integer object, which is a bignum only if it is out of
+    /// fixnum range.
+    #[cfg(feature = "bignum")]
+    pub(crate) fn bigint(&mut self, v: BigInt) -> Object {
+        self.make_num(Num::from_big(v))
+    }
+
+    /// Create a number object from its numeric view.
+    fn make_num(&mut self, n: Num) -> Object {
+        match n {
+            Num::Int(v) => self.int(v),
+            Num::Float(v) => self.float(v),
+            Num::Rat(n, d) => self.alloc(HVal::Rational(n, d)),
+            #[cfg(feature = "bignum")]
+            Num::Big(v) => self.alloc(HVal::BigInt(v)),
+        }
+    }
+}
+
+
+/// Normalize a fraction to lowest terms with a positive denominator.
+/// Returns `None` if the denominator is zero or the result is out of
+/// range.
+fn ratio(num: i128, den: i128) -> Option<Num> {
+    if den == 0 {
+        return None;
+    }
+
+    let g = gcd(num, den);
+    let (num, den) = if den < 0 { (-num / g, -den / g) } else { (num / g, den / g) };
+    let num = isize::try_from(num).ok()?;
+    match isize::try_from(den).ok()? {
+        1 => Some(Num::Int(num)),
+        den => Some(Num::Rat(num, den)),
+    }
+}
+
+/// Like `ratio`, but for arbitrary precision parts.  Whole results
+/// may be bignums, but other results must fit a fixnum rational.
+#[cfg(feature = "bignum")]
+fn big_ratio(num: BigInt, den: BigInt) -> Option<Num> {
+    if den.is_zero() {
+        return None;
+    }
+
+    let g = big_gcd(num.clone(), den.clone());
+    let (num, den) = if den.is_negative() { (-num / &g, -den / &g) } else { (num / &g, den / &g) };
+    if den.is_one() {
+        return Some(Num::from_big(num));
+    }
+    Some(Num::Rat(isize::try_from(&num).ok()?, isize::try_from(&den).ok()?))
+}
+
+#[cfg(feature = "bignum")]
+fn big_gcd(a: BigInt, b: BigInt) -> BigInt {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while !b.is_zero() {
+        let t = &a % &b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Checked integer exponentiation for non-negative exponents.
+fn int_pow(base: isize, exp: isize) -> Option<isize> {
+    match base {
+        0 if exp == 0 => Some(1),
+        0 | 1 => Some(base),
+        -1 => Some(if exp % 2 == 0 { 1 } else { -1 }),
+        _ => base.checked_pow(u32::try_from(exp).ok()?),
+    }
+}
+
+/// Format an integer in a radix between 2 and 36.
+/// Prefix the reader uses for a radix, such as `#x` for 16.  Returns
+/// `None` for radices the reader does not support.
+pub(crate) fn radix_prefix(radix: u32) -> Option<&'static str> {
+    match radix {
+        2 => Some("#b"),
+        8 => Some("#o"),
+        10 => Some(""),
+        16 => Some("#x"),
+        _ => None,
+    }
+}
+
+pub(crate) fn int_to_radix(v: isize, radix: u32) -> String {
+    let mut digits = Vec::new();
+    let mut rest = v.unsigned_abs();
+    loop {
+        digits.push(from_digit((rest % radix as usize) as u32, radix).unwrap());
+        rest /= radix as usize;
+        if rest == 0 {
+            break;
+        }
+    }
+    if v < 0 {
+        digits.push('-');
+    }
+    digits.iter().rev().collect()
+}
+
+
+/// Convert an integral float to an integer, if it is in range.
+fn float_to_int(v: f64) -> Option<isize> {
+    if v.fract() == 0.0 && v >= isize::MIN as f64 && v < isize::MAX as f64 {
+        Some(v as isize)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{DObj, GObj, HVal, Object, VM};
+
+
+impl VM {
+    /// Create and return a new promise, which calls a procedure with no
+    /// arguments the first time it is forced.  Returns `None` if the
+    /// object is not a procedure.
+    pub fn delay(&mut self, thunk: impl GObj) -> Option<Object> {
+        let thunk = thunk.unroot();
+        match self.direct(thunk) {
+            DObj::H(HVal::Native(_)) | DObj::H(HVal::Closure { .. }) => {
+                Some(self.alloc(HVal::Promise { thunk, value: None }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Force a promise, like Scheme's `force`.  The procedure of the
+    /// promise is called the first time, and its result is remembered
+    /// and returned by every later call.  Other objects are returned
+    /// as is.  Returns `None` if the call fails, in which case the
+    /// promise is left unforced.
+    pub fn force(&mut self, obj: impl GObj) -> Option<Object> {
+        let promise = obj.unroot();
+        let thunk = match self.direct(promise) {
+            DObj::H(&HVal::Promise { value: Some(value), .. }) => return Some(value),
+            DObj::H(&HVal::Promise { thunk, value: None }) => thunk,
+            _ => return Some(promise),
+        };
+
+        let _rooted = promise.root(self);
+        let result = self.apply(thunk, &[])?;
+
+        // The thunk may have forced the promise itself, in which case
+        // the first result wins
+        match self.direct_mut(promise) {
+            DObj::H(HVal::Promise { value: Some(value), .. }) => Some(*value),
+            DObj::H(HVal::Promise { value, .. }) => {
+                *value = Some(result);
+                Some(result)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Check whether an object is a promise.
+    pub fn is_promise(&self, obj: impl GObj) -> bool {
+        matches!(self.direct(obj), DObj::H(HVal::Promise { .. }))
+    }
+}
@@ -0,0 +1,530 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+
+use crate::{GinkgoError, GObj, HVal, Object, RootedObject, VM};
+use crate::string::{parse_char, unescape};
+
+
+/// Error raised when source text cannot be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadError {
+    /// Byte offset of the error in the source.
+    pub offset: usize,
+    /// Line number of the error, starting at 1.
+    pub line: usize,
+    /// Column number of the error in characters, starting at 1.
+    pub column: usize,
+    /// The offending part of the source.
+    pub snippet: String,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl ReadError {
+    fn new(src: &str, start: usize, end: usize, message: &str) -> ReadError {
+        let before = &src[..start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ReadError {
+            offset: start,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            snippet: src[start..end].to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ReadError {}
+
+
+/// Outcome of reading a datum from input that may not be complete yet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadOutcome {
+    /// A complete datum was read.
+    Complete(Object),
+    /// The input ended in the middle of a datum.
+    Incomplete,
+    /// The input is malformed.
+    Error(ReadError),
+}
+
+
+/// Recursive descent parser over a source string.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// Whether the last error was caused by running out of input, or
+    /// the input ran out inside a comment.
+    eof: bool,
+    /// Number of enclosing lists, vectors and quotes.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, start: usize, message: &str) -> ReadError {
+        ReadError::new(self.src, start, self.pos, message)
+    }
+
+    /// Report a failure to allocate the datum starting at the given
+    /// offset, such as exceeding the heap limit.
+    fn alloc_error(&self, start: usize, err: GinkgoError) -> ReadError {
+        self.error(start, &err.to_string())
+    }
+
+    fn eof_error(&mut self, start: usize, message: &str) -> ReadError {
+        self.eof = true;
+        self.error(start, message)
+    }
+
+    /// Skip whitespace and comments, as well as a `#!` line at the very
+    /// start of the source, so that scripts may begin with a shebang.
+    fn skip_atmosphere(&mut self) {
+        if self.pos == 0 && self.src.starts_with("#!") {
+            self.skip_line();
+        }
+        while let Some(c) = self.peek() {
+            if c == ';' {
+                self.skip_line();
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skip the rest of a line, including the newline.
+    fn skip_line(&mut self) {
+        loop {
+            match self.bump() {
+                Some('\n') => break,
+                None => {
+                    self.eof = true;
+                    break;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Consume characters up to the next delimiter.
+    fn token(&mut self) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || DELIMITERS.contains(c) {
+                break;
+            }
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Read a datum, failing if lists, vectors and quotes are nested
+    /// deeper than the VM allows, so that hostile input cannot overflow
+    /// the stack.
+    fn datum(&mut self, vm: &mut VM) -> Result<Object, ReadError> {
+        self.skip_atmosphere();
+        let rest = &self.src[self.pos..];
+        let nested = rest.starts_with(['(', '\'']) || rest.starts_with("#(");
+        if !nested {
+            return self.parse_datum(vm);
+        }
+        if self.depth >= vm.max_read_depth {
+            let start = self.pos;
+            self.bump();
+            return Err(self.error(start, "maximum nesting depth exceeded"));
+        }
+        self.depth += 1;
+        let result = self.parse_datum(vm);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_datum(&mut self, vm: &mut VM) -> Result<Object, ReadError> {
+        let start = self.pos;
+        match self.bump() {
+            None => Err(self.eof_error(start, "unexpected end of input")),
+            Some('(') => self.list(vm, start),
+            Some(')') => Err(self.error(start, "unexpected ')'")),
+            Some('\'') => {
+                let datum = self.datum(vm)?;
+                let quote = vm.symbol("quote");
+                vm.try_prepend(&[quote, datum], Object::Nil).map_err(|err| self.alloc_error(start, err))
+            }
+            Some('"') => self.string(vm, start),
+            Some('|') => self.bar_symbol(vm, start),
+            Some('#') if self.peek() == Some('\\') => {
+                self.bump();
+                if self.bump().is_none() {
+                    return Err(self.eof_error(start, "unexpected end of input"));
+                }
+                self.token();
+                match parse_char(&self.src[start + 2..self.pos]) {
+                    Some(c) => Ok(vm.char(c)),
+                    None => Err(self.error(start, "unknown character name")),
+                }
+            }
+            Some('#') if self.peek() == Some('(') => {
+                self.bump();
+                let items = self.items(vm, start)?;
+                let items = items.iter().map(GObj::unroot).collect();
+                vm.try_alloc(HVal::Vec(Rc::new(items))).map_err(|err| self.alloc_error(start, err))
+            }
+            Some('#') => match self.token() {
+                "" if self.peek().is_none() => Err(self.eof_error(start, "unexpected end of input")),
+                "t" | "true" => Ok(Object::True),
+                "f" | "false" => Ok(Object::False),
+                _ => match number(vm, &self.src[start..self.pos]) {
+                    Some(result) => result.map_err(|msg| self.error(start, msg)),
+                    None => Err(self.error(start, "unknown # syntax")),
+                },
+            },
+            Some(_) => {
+                self.pos = start;
+                let token = self.token();
+                self.atom(vm, start, token)
+            }
+        }
+    }
+
+    /// Read the elements of a list after its opening parenthesis,
+    /// including a dotted tail if present.  The elements are rooted
+    /// until the list is built, since reading the next one may collect
+    /// garbage.
+    fn list(&mut self, vm: &mut VM, start: usize) -> Result<Object, ReadError> {
+        let mut items = Vec::new();
+        let mut tail = Object::Nil.root(vm);
+        loop {
+            self.skip_atmosphere();
+            match self.peek() {
+                None => return Err(self.eof_error(start, "unclosed list")),
+                Some(')') => {
+                    self.bump();
+                    break;
+                }
+                _ => {}
+            }
+
+            let dot = self.pos;
+            if self.token() == "." {
+                if items.is_empty() {
+                    return Err(self.error(dot, "expected a datum before dot"));
+                }
+                self.skip_atmosphere();
+                let after = self.pos;
+                match self.peek() {
+                    None => return Err(self.eof_error(start, "unclosed list")),
+                    Some(')') => return Err(self.error(after, "expected exactly one datum after dot")),
+                    _ if self.token() == "." => return Err(self.error(after, "expected exactly one datum after dot")),
+                    _ => self.pos = after,
+                }
+                tail = self.datum(vm)?.root(vm);
+                self.skip_atmosphere();
+                let close = self.pos;
+                match self.bump() {
+                    Some(')') => break,
+                    None => return Err(self.eof_error(start, "unclosed list")),
+                    Some(_) => return Err(self.error(close, "expected exactly one datum after dot")),
+                }
+            }
+            self.pos = dot;
+            items.push(self.datum(vm)?.root(vm));
+        }
+
+        let items: Vec<Object> = items.iter().map(GObj::unroot).collect();
+        vm.try_prepend(&items, tail.unroot()).map_err(|err| self.alloc_error(start, err))
+    }
+
+    /// Read the elements of a vector after its opening parenthesis,
+    /// rooted as in `list`.
+    fn items(&mut self, vm: &mut VM, start: usize) -> Result<Vec<RootedObject>, ReadError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_atmosphere();
+            match self.peek() {
+                None => return Err(self.eof_error(start, "unclosed vector")),
+                Some(')') => {
+                    self.bump();
+                    return Ok(items);
+                }
+                _ => items.push(self.datum(vm)?.root(vm)),
+            }
+        }
+    }
+
+    fn string(&mut self, vm: &mut VM, start: usize) -> Result<Object, ReadError> {
+        loop {
+            match self.bump() {
+                None => return Err(self.eof_error(start, "unclosed string")),
+                Some('"') => break,
+                Some('\\') => self.skip_escape(),
+                Some(_) => {}
+            }
+        }
+        let body = &self.src[start + 1..self.pos - 1];
+        let s = unescape(body).ok_or_else(|| self.error(start, "invalid escape sequence"))?;
+        vm.try_string(s).map_err(|err| self.alloc_error(start, err))
+    }
+
+    /// Skip the rest of an escape sequence after a backslash.  A caret
+    /// escape is skipped as a whole, since its second character may be
+    /// a backslash, as in `\^\` for the character 0x1c.
+    fn skip_escape(&mut self) {
+        if self.bump() == Some('^') {
+            self.bump();
+        }
+    }
+
+    /// Parse a number, symbol or `nil`.  Fails for a lone dot and for
+    /// malformed numbers.
+    fn atom(&self, vm: &mut VM, start: usize, token: &str) -> Result<Object, ReadError> {
+        match token {
+            "." => return Err(self.error(start, "unexpected '.'")),
+            "nil" => return Ok(Object::Nil),
+            _ => {}
+        }
+        match number(vm, token) {
+            Some(result) => result.map_err(|msg| self.error(start, msg)),
+            None => Ok(vm.symbol(token)),
+        }
+    }
+
+    /// Read a symbol written between vertical bars, after the opening
+    /// bar.
+    fn bar_symbol(&mut self, vm: &mut VM, start: usize) -> Result<Object, ReadError> {
+        loop {
+            match self.bump() {
+                None => return Err(self.eof_error(start, "unclosed symbol")),
+                Some('|') => break,
+                Some('\\') => self.skip_escape(),
+                Some(_) => {}
+            }
+        }
+        let body = &self.src[start + 1..self.pos - 1];
+        let name = unescape(body).ok_or_else(|| self.error(start, "invalid escape sequence"))?;
+        Ok(vm.symbol(&name))
+    }
+}
+
+
+/// Characters that end a token.
+const DELIMITERS: &str = "()\";'|";
+
+/// Numeric literal, before it is turned into an object.
+enum Number {
+    Int(isize),
+    #[cfg(feature = "bignum")]
+    Big(BigInt),
+    Float(f64),
+    Rational(isize, isize),
+}
+
+/// Result of parsing a token that looks like a number, with an error
+/// message if it is malformed.
+type NumberResult<T> = Result<T, &'static str>;
+
+/// Parse a token as a number and create it.  Returns `None` if the
+/// token is not a number, and an error for integers out of range and
+/// rationals with a zero denominator.
+pub(crate) fn number(vm: &mut VM, token: &str) -> Option<NumberResult<Object>> {
+    Some(match parse_number(token)? {
+        Ok(Number::Int(v)) => Ok(vm.int(v)),
+        #[cfg(feature = "bignum")]
+        Ok(Number::Big(v)) => Ok(vm.bigint(v)),
+        Ok(Number::Float(v)) => Ok(vm.float(v)),
+        Ok(Number::Rational(n, d)) => vm.rational(n, d).ok_or("zero denominator"),
+        Err(msg) => Err(msg),
+    })
+}
+
+/// Parse a token as a number, if it looks like one.  A radix prefix
+/// of `#x`, `#o`, `#b` or `#d` allows exact integers and rationals to
+/// be written in that radix.
+fn parse_number(token: &str) -> Option<NumberResult<Number>> {
+    let rest = match token.strip_prefix('#') {
+        Some(rest) => rest,
+        None => return parse_decimal(token),
+    };
+    let (prefix, digits) = (rest.get(..1)?, &rest[1..]);
+    let radix = match prefix {
+        "x" | "X" => 16,
+        "o" | "O" => 8,
+        "b" | "B" => 2,
+        "d" | "D" => 10,
+        _ => return None,
+    };
+    match digits.split_once('/') {
+        None => parse_integer(digits, radix),
+        Some((n, d)) if !d.starts_with(['+', '-']) => parse_rational(n, d, radix),
+        Some(_) => None,
+    }
+}
+
+/// Parse a token as a decimal number, if it looks like one.
+fn parse_decimal(token: &str) -> Option<NumberResult<Number>> {
+    match token {
+        "+inf.0" => return Some(Ok(Number::Float(f64::INFINITY))),
+        "-inf.0" => return Some(Ok(Number::Float(f64::NEG_INFINITY))),
+        "+nan.0" | "-nan.0" => return Some(Ok(Number::Float(f64::NAN))),
+        _ => {}
+    }
+    let digits = token.trim_start_matches(['+', '-']).trim_start_matches('.');
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if let Some(v) = parse_integer(token, 10) {
+        return Some(v);
+    }
+    if let Ok(v) = token.parse() {
+        return Some(Ok(Number::Float(v)));
+    }
+    match token.split_once('/')? {
+        (n, d) if !d.starts_with(['+', '-']) => parse_rational(n, d, 10),
+        _ => None,
+    }
+}
+
+/// Parse an integer literal.  With the `bignum` feature, integers out
+/// of fixnum range become bignums.
+fn parse_integer(digits: &str, radix: u32) -> Option<NumberResult<Number>> {
+    match parse_fixnum(digits, radix)? {
+        Ok(v) => Some(Ok(Number::Int(v))),
+        #[cfg(feature = "bignum")]
+        Err(_) => BigInt::parse_bytes(digits.as_bytes(), radix).map(|v| Ok(Number::Big(v))),
+        #[cfg(not(feature = "bignum"))]
+        Err(msg) => Some(Err(msg)),
+    }
+}
+
+/// Parse a rational literal from its numerator and denominator.
+fn parse_rational(n: &str, d: &str, radix: u32) -> Option<NumberResult<Number>> {
+    let (n, d) = (parse_fixnum(n, radix)?, parse_fixnum(d, radix)?);
+    Some(n.and_then(|n| Ok(Number::Rational(n, d?))))
+}
+
+/// Parse an integer that must fit in a fixnum.  Returns `None` if the
+/// digits are not an integer at all.
+fn parse_fixnum(digits: &str, radix: u32) -> Option<NumberResult<isize>> {
+    let unsigned = digits.strip_prefix(['+', '-']).unwrap_or(digits);
+    if unsigned.is_empty() || !unsigned.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    Some(isize::from_str_radix(digits, radix).map_err(|_| "integer literal out of range"))
+}
+
+/// Check whether a symbol name can be written as is, without vertical
+/// bars, and still read back as the same symbol.  This errs on the
+/// side of caution.
+pub(crate) fn is_plain_symbol(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('#')
+        && name != "."
+        && name != "nil"
+        && name.chars().all(|c| !c.is_whitespace() && !c.is_control() && !DELIMITERS.contains(c))
+        && parse_number(name).is_none()
+}
+
+/// Incremental reader producing one datum at a time from a source
+/// that may grow as more input arrives.
+pub struct Reader<'a> {
+    src: Cow<'a, str>,
+    pos: usize,
+    /// Whether the source ended inside a comment or a `#!` line, which
+    /// must be skipped up to the next newline once more text arrives.
+    in_comment: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Create a reader over borrowed or owned source text.
+    pub fn new(src: impl Into<Cow<'a, str>>) -> Reader<'a> {
+        Reader { src: src.into(), pos: 0, in_comment: false }
+    }
+
+    /// Append more text to the source.
+    pub fn feed(&mut self, text: &str) {
+        self.src.to_mut().push_str(text);
+    }
+
+    /// Byte offset of the cursor in the source.
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// Read the next datum, or return `None` if only whitespace and
+    /// comments remain.  If the datum is cut off by the end of the
+    /// source, the cursor is left in place so that it can be read
+    /// again after feeding more text.  Errors move the cursor past the
+    /// offending text.
+    pub fn next_datum(&mut self, vm: &mut VM) -> Option<ReadOutcome> {
+        if self.in_comment {
+            match self.src[self.pos..].find('\n') {
+                Some(i) => {
+                    self.pos += i + 1;
+                    self.in_comment = false;
+                }
+                None => {
+                    self.pos = self.src.len();
+                    return None;
+                }
+            }
+        }
+
+        let mut parser = Parser { src: &self.src, pos: self.pos, eof: false, depth: 0 };
+        parser.skip_atmosphere();
+        if parser.pos == self.src.len() {
+            self.pos = parser.pos;
+            self.in_comment = parser.eof;
+            return None;
+        }
+
+        Some(match parser.datum(vm) {
+            Ok(datum) => {
+                self.pos = parser.pos;
+                ReadOutcome::Complete(datum)
+            }
+            Err(_) if parser.eof => ReadOutcome::Incomplete,
+            Err(err) => {
+                self.pos = parser.pos;
+                ReadOutcome::Error(err)
+            }
+        })
+    }
+}
+
+
+impl VM {
+    /// Read a single datum from source text.  The datum may be
+    /// surrounded by whitespace and comments, but nothing else.
+    pub fn read(&mut self, src: &str) -> Result<Object, ReadError> {
+        let mut parser = Parser { src, pos: 0, eof: false, depth: 0 };
+        let datum = parser.datum(self)?;
+        parser.skip_atmosphere();
+        if parser.pos < src.len() {
+            let start = parser.pos;
+            parser.pos = src.len();
+            return Err(parser.error(start, "unexpected trailing input"));
+        }
+        Ok(datum)
+    }
+}
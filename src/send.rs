@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use broom::Handle;
+
+use crate::{GObj, HVal, NativeFn, Object, RootedObject, SVal, VM};
+
+
+/// VM state that can be moved between threads.  Obtained from
+/// `VM::into_sendable` and turned back into a VM with `into_vm`.
+///
+/// A `VM` is not `Send` itself: heap handles are raw pointers, and
+/// roots and vector storage are reference counted.
+pub struct SendableVm {
+    symbols: Vec<String>,
+    nodes: Vec<NodeEntry>,
+    roots: Vec<Value>,
+    gc_threshold: usize,
+    auto_gc: bool,
+    heap_limit: Option<usize>,
+    nil_is_false: bool,
+    undefined_display: &'static str,
+    hide_undefined: bool,
+    print_radix: u32,
+    max_read_depth: usize,
+}
+
+/// An object, with heap objects given as indices into the node list.
+#[derive(Clone, Copy)]
+enum Value {
+    S(SVal),
+    H(usize),
+}
+
+/// A heap value, with its frozen flag and tag.
+type NodeEntry = (Node, bool, Option<u32>);
+
+/// A heap value, with references given as values.
+enum Node {
+    Cons(Value, Value),
+    Vec(Vec<Value>),
+    String(String),
+    Rational(isize, isize),
+    #[cfg(feature = "bignum")]
+    BigInt(num_bigint::BigInt),
+    Native(NativeFn),
+    Env { vars: Vec<(usize, Value)>, parent: Option<Value> },
+    Closure { params: Value, body: Value, env: Value },
+    Error { message: String, irritants: Vec<Value> },
+    Values(Vec<Value>),
+    Promise { thunk: Value, value: Option<Value> },
+}
+
+impl SendableVm {
+    /// Rebuild the VM, returning it along with the objects that were
+    /// passed to `VM::into_sendable`, rooted and in the same order.
+    pub fn into_vm(self) -> (VM, Vec<RootedObject>) {
+        let mut vm = VM {
+            symbol_ids: self.symbols.iter().cloned().enumerate().map(|(id, name)| (name, id)).collect(),
+            symbols: self.symbols,
+            gc_threshold: self.gc_threshold,
+            next_gc: self.gc_threshold,
+            auto_gc: self.auto_gc,
+            heap_limit: self.heap_limit,
+            nil_is_false: self.nil_is_false,
+            undefined_display: self.undefined_display,
+            hide_undefined: self.hide_undefined,
+            print_radix: self.print_radix,
+            max_read_depth: self.max_read_depth,
+            ..VM::new()
+        };
+
+        // Allocate every object first, so that references can be filled in
+        let handles: Vec<Handle<HVal>> = self.nodes.iter()
+            .map(|_| vm.insert(HVal::Vec(Rc::default())))
+            .collect();
+        let obj = |v: Value| match v {
+            Value::S(v) => Object::S(v),
+            Value::H(i) => Object::H(handles[i]),
+        };
+
+        for ((node, frozen, tag), &handle) in self.nodes.into_iter().zip(&handles) {
+            let val = match node {
+                Node::Cons(car, cdr) => HVal::Cons(obj(car), obj(cdr)),
+                Node::Vec(items) => HVal::Vec(Rc::new(items.into_iter().map(obj).collect())),
+                Node::String(s) => HVal::String(s),
+                Node::Rational(n, d) => HVal::Rational(n, d),
+                #[cfg(feature = "bignum")]
+                Node::BigInt(v) => HVal::BigInt(v),
+                Node::Native(f) => HVal::Native(f),
+                Node::Env { vars, parent } => HVal::Env {
+                    vars: vars.into_iter().map(|(id, v)| (id, obj(v))).collect(),
+                    parent: parent.map(obj),
+                },
+                Node::Closure { params, body, env } =>
+                    HVal::Closure { params: obj(params), body: obj(body), env: obj(env) },
+                Node::Error { message, irritants } => HVal::Error {
+                    message,
+                    irritants: irritants.into_iter().map(obj).collect(),
+                },
+                Node::Values(values) => HVal::Values(values.into_iter().map(obj).collect()),
+                Node::Promise { thunk, value } => HVal::Promise { thunk: obj(thunk), value: value.map(obj) },
+            };
+            *vm.heap.get_mut(handle).unwrap() = val;
+            if frozen {
+                vm.frozen.insert(handle);
+            }
+            if let Some(tag) = tag {
+                vm.tags.insert(handle, tag);
+            }
+        }
+
+        // Sizes were estimated for the placeholders
+        vm.sizes = handles.iter()
+            .map(|&handle| (handle, crate::size::value_size(vm.heap.get(handle).unwrap())))
+            .collect();
+        vm.heap_bytes = vm.sizes.values().sum();
+
+        let roots = self.roots.into_iter().map(|v| obj(v).root(&mut vm)).collect();
+        (vm, roots)
+    }
+}
+
+
+impl VM {
+    /// Convert the VM into a form that can be moved to another thread,
+    /// keeping the given objects and everything reachable from them.
+    /// Symbols, configuration, frozen flags and tags are kept; the
+    /// allocation hook and weak references are not.  Fails if a
+    /// foreign object is reachable, since its value may not be safe to
+    /// send, or if a root is dead, returning the VM unchanged.
+    pub fn into_sendable(self, roots: &[Object]) -> Result<SendableVm, Box<VM>> {
+        let (nodes, roots) = match self.sendable_nodes(roots) {
+            Some(result) => result,
+            None => return Err(Box::new(self)),
+        };
+        Ok(SendableVm {
+            symbols: self.symbols,
+            nodes,
+            roots,
+            gc_threshold: self.gc_threshold,
+            auto_gc: self.auto_gc,
+            heap_limit: self.heap_limit,
+            nil_is_false: self.nil_is_false,
+            undefined_display: self.undefined_display,
+            hide_undefined: self.hide_undefined,
+            print_radix: self.print_radix,
+            max_read_depth: self.max_read_depth,
+        })
+    }
+
+    /// Convert the objects reachable from the given roots into nodes,
+    /// returning the nodes and the converted roots.
+    fn sendable_nodes(&self, roots: &[Object]) -> Option<(Vec<NodeEntry>, Vec<Value>)> {
+        let mut indices: HashMap<Handle<HVal>, usize> = HashMap::new();
+        let mut pending = Vec::new();
+        let mut nodes = Vec::new();
+
+        let mut value = |obj: Object, pending: &mut Vec<Handle<HVal>>| match obj {
+            Object::S(v) => Value::S(v),
+            Object::H(handle) => {
+                let next = indices.len();
+                Value::H(*indices.entry(handle).or_insert_with(|| {
+                    pending.push(handle);
+                    next
+                }))
+            }
+        };
+
+        let roots = roots.iter().map(|&root| value(root, &mut pending)).collect();
+
+        // Handles are queued in index order, so nodes end up in place
+        let mut next = 0;
+        while next < pending.len() {
+            let handle = pending[next];
+            next += 1;
+            let node = match self.heap.get(handle)? {
+                &HVal::Cons(car, cdr) => Node::Cons(value(car, &mut pending), value(cdr, &mut pending)),
+                HVal::Vec(items) => Node::Vec(items.iter().map(|&item| value(item, &mut pending)).collect()),
+                HVal::String(s) => Node::String(s.clone()),
+                &HVal::Rational(n, d) => Node::Rational(n, d),
+                #[cfg(feature = "bignum")]
+                HVal::BigInt(v) => Node::BigInt(v.clone()),
+                &HVal::Native(f) => Node::Native(f),
+                HVal::Foreign(_) => return None,
+                HVal::Env { vars, parent } => Node::Env {
+                    vars: vars.iter().map(|(&id, &val)| (id, value(val, &mut pending))).collect(),
+                    parent: parent.map(|p| value(p, &mut pending)),
+                },
+                &HVal::Closure { params, body, env } => Node::Closure {
+                    params: value(params, &mut pending),
+                    body: value(body, &mut pending),
+                    env: value(env, &mut pending),
+                },
+                HVal::Error { message, irritants } => Node::Error {
+                    message: message.clone(),
+                    irritants: irritants.iter().map(|&item| value(item, &mut pending)).collect(),
+                },
+                HVal::Values(values) => Node::Values(values.iter().map(|&item| value(item, &mut pending)).collect()),
+                &HVal::Promise { thunk, value: cached } => Node::Promise {
+                    thunk: value(thunk, &mut pending),
+                    value: cached.map(|v| value(v, &mut pending)),
+                },
+            };
+            nodes.push((node, self.frozen.contains(&handle), self.tags.get(&handle).copied()));
+        }
+        Some((nodes, roots))
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use std::mem::size_of;
+
+use broom::Handle;
+
+use crate::{DObj, GinkgoError, GObj, HVal, Object, VM};
+
+
+/// Estimate the number of bytes occupied by a heap value.
+pub(crate) fn value_size(val: &HVal) -> usize {
+    let owned = match val {
+        HVal::Vec(items) => items.capacity() * size_of::<Object>(),
+        HVal::String(s) => s.capacity(),
+        HVal::Env { vars, .. } => vars.capacity() * size_of::<(usize, Object)>(),
+        HVal::Error { message, irritants } => message.capacity() + irritants.capacity() * size_of::<Object>(),
+        HVal::Values(values) => values.capacity() * size_of::<Object>(),
+        #[cfg(feature = "bignum")]
+        HVal::BigInt(v) => (v.bits() as usize).div_ceil(64) * 8,
+        HVal::Cons(..) | HVal::Rational(..) | HVal::Native(_) | HVal::Foreign(_)
+            | HVal::Closure { .. } | HVal::Promise { .. } => 0,
+    };
+    size_of::<HVal>() + owned
+}
+
+
+impl VM {
+    /// Estimate the number of bytes occupied by a heap object: the
+    /// size of the value itself plus any buffer it owns, such as the
+    /// characters of a string or the slots of a vector.  Allocator and
+    /// garbage collector overhead is not counted, and elements shared
+    /// between vectors are counted for each of them.  Stack values
+    /// occupy no heap space and give zero.
+    pub fn size_of(&self, obj: impl GObj) -> usize {
+        match self.direct(obj) {
+            DObj::H(val) => value_size(val),
+            _ => 0,
+        }
+    }
+
+    /// Estimate the number of bytes occupied by an object and
+    /// everything reachable from it, as by `size_of`.  Shared and
+    /// cyclic structure is counted once.
+    pub fn deep_size(&self, obj: impl GObj) -> usize {
+        let mut visited = HashSet::new();
+        let mut stack = vec![obj.unroot()];
+        let mut total = 0;
+
+        while let Some(obj) = stack.pop() {
+            let handle = match obj {
+                Object::H(handle) => handle,
+                Object::S(_) => continue,
+            };
+            if !visited.insert(handle) {
+                continue;
+            }
+            total += self.size_of(obj);
+            match self.direct(obj) {
+                DObj::H(HVal::Cons(car, cdr)) => stack.extend([*car, *cdr]),
+                DObj::H(HVal::Vec(items)) => stack.extend(items.iter()),
+                DObj::H(HVal::Env { vars, parent }) => {
+                    stack.extend(vars.values());
+                    stack.extend(parent);
+                }
+                DObj::H(HVal::Closure { params, body, env }) => stack.extend([*params, *body, *env]),
+                DObj::H(HVal::Error { irritants, .. }) => stack.extend(irritants),
+                DObj::H(HVal::Values(values)) => stack.extend(values),
+                DObj::H(HVal::Promise { thunk, value }) => {
+                    stack.push(*thunk);
+                    stack.extend(value);
+                }
+                _ => {}
+            }
+        }
+
+        total
+    }
+
+    /// Limit the estimated number of bytes occupied by the heap, as
+    /// measured by `size_of` when each object is allocated, and again
+    /// when vectors, strings and environments grow in place.  When an
+    /// allocation or growth takes the heap over the limit, a garbage
+    /// collection runs, so any unrooted object the caller holds may be
+    /// freed.  If the heap is still over the limit, the operation
+    /// fails: `try_cons`, `try_vec`, `try_string` and `try_make_env`
+    /// return an error, as do `vec_push`, `string_set` and
+    /// `env_define`, which undo the growth first, and `eval` and
+    /// `read`.  Other allocating operations return undefined.
+    pub fn set_heap_limit(&mut self, bytes: usize) {
+        self.heap_limit = Some(bytes);
+    }
+
+    /// Remove the heap limit.  There is no limit by default.
+    pub fn remove_heap_limit(&mut self) {
+        self.heap_limit = None;
+    }
+
+    /// Record the new size of a heap object that has grown in place.
+    /// If the heap is over the limit, garbage is collected, and if it
+    /// is still over, the change is undone and the operation fails.
+    pub(crate) fn grown(&mut self, obj: Object, undo: impl FnOnce(&mut HVal)) -> Result<(), GinkgoError> {
+        let handle = match obj {
+            Object::H(handle) => handle,
+            Object::S(_) => return Ok(()),
+        };
+        self.update_size(handle);
+        if let Err(err) = self.collect_after(handle) {
+            if let Some(val) = self.heap.get_mut(handle) {
+                undo(val);
+            }
+            self.update_size(handle);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn update_size(&mut self, handle: Handle<HVal>) {
+        let size = match self.heap.get(handle) {
+            Some(val) => value_size(val),
+            None => return,
+        };
+        let old = self.sizes.insert(handle, size).unwrap_or(0);
+        self.heap_bytes = self.heap_bytes + size - old;
+    }
+
+    /// Return the estimated number of bytes occupied by the heap.
+    /// Includes garbage not yet collected.
+    pub fn heap_bytes(&self) -> usize {
+        self.heap_bytes
+    }
+}
@@ -1,7 +1,165 @@
 use std::str::Chars;
 use std::char::from_u32;
 
-#[allow(dead_code)]
+use crate::{DObj, GinkgoError, GObj, HVal, Object, VM};
+
+
+impl VM {
+    /// Get the contents of a string object.
+    pub(crate) fn as_str(&self, obj: impl GObj) -> Option<&str> {
+        match self.direct(obj) {
+            DObj::H(HVal::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Create and return a new empty string.
+    pub fn empty_string(&mut self) -> Object {
+        self.string(String::new())
+    }
+
+    /// Create and return a new string of `len` copies of a character,
+    /// like Scheme's `make-string`.
+    pub fn make_string(&mut self, len: usize, fill: char) -> Object {
+        self.string(fill.to_string().repeat(len))
+    }
+
+    /// Return a new string with the same contents as a string, like
+    /// Scheme's `string-copy`.
+    pub fn string_copy(&mut self, obj: impl GObj) -> Option<Object> {
+        let s = self.as_str(obj)?.to_string();
+        Some(self.string(s))
+    }
+
+    /// Replace the character at a given character index in a string,
+    /// like Scheme's `string-set!`.  The new character may have a
+    /// different encoded length than the old one.  Fails with
+    /// `GinkgoError::OutOfMemory`, leaving the string unchanged, if
+    /// growing it exceeds the heap limit.
+    pub fn string_set(&mut self, obj: impl GObj, index: usize, c: char) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
+        let (start, old, capacity) = match self.direct_mut(obj) {
+            DObj::H(HVal::String(s)) => {
+                let (start, old) = s.char_indices().nth(index).ok_or_else(|| {
+                    GinkgoError::IndexOutOfBounds { index, len: s.chars().count(), kind: "string" }
+                })?;
+                let capacity = s.capacity();
+                s.replace_range(start..start + old.len_utf8(), c.encode_utf8(&mut [0; 4]));
+                (start, old, capacity)
+            }
+            _ => return Err(GinkgoError::TypeMismatch("string")),
+        };
+        self.grown(obj, |val| if let HVal::String(s) = val {
+            s.replace_range(start..start + c.len_utf8(), old.encode_utf8(&mut [0; 4]));
+            s.shrink_to(capacity);
+        })
+    }
+
+    /// Return a list of the characters in a string.
+    pub fn string_chars(&mut self, obj: impl GObj) -> Option<Object> {
+        let chars: Vec<Object> = self.as_str(obj)?.chars().map(|c| self.char(c)).collect();
+        Some(self.list(&chars))
+    }
+
+    /// Convert a string to a list of characters, like Scheme's
+    /// `string->list`.  Same as `string_chars`.
+    pub fn string_to_list(&mut self, obj: impl GObj) -> Option<Object> {
+        self.string_chars(obj)
+    }
+
+    /// Convert a list of characters to a string, like Scheme's
+    /// `list->string`.  Returns `None` if the list is improper or has
+    /// elements that are not characters.
+    pub fn list_to_string(&mut self, obj: impl GObj) -> Option<Object> {
+        let s = self.list_to_vec(obj)?.iter().map(GObj::as_char).collect::<Option<String>>()?;
+        Some(self.string(s))
+    }
+
+    /// Concatenate the strings in a list, with a separator string
+    /// between each.  Returns `None` if the list is improper or if the
+    /// separator or any element is not a string.
+    pub fn string_join(&mut self, list: impl GObj, sep: impl GObj) -> Option<Object> {
+        let sep = self.as_str(sep)?;
+        let parts = self.list_to_vec(list)?.into_iter()
+            .map(|item| self.as_str(item))
+            .collect::<Option<Vec<&str>>>()?;
+        let s = parts.join(sep);
+        Some(self.string(s))
+    }
+
+    /// Split a string on a separator string, returning a list of the
+    /// pieces.  Adjacent separators give empty strings between them.
+    /// An empty separator splits the string into one-character
+    /// strings.  Returns `None` if either argument is not a string.
+    pub fn string_split(&mut self, obj: impl GObj, sep: impl GObj) -> Option<Object> {
+        let (s, sep) = (self.as_str(obj)?, self.as_str(sep)?);
+        let parts: Vec<String> = if sep.is_empty() {
+            s.chars().map(String::from).collect()
+        } else {
+            s.split(sep).map(String::from).collect()
+        };
+        let mut rooted = Vec::with_capacity(parts.len());
+        for part in parts {
+            rooted.push(self.string(part).root(self));
+        }
+        let parts: Vec<Object> = rooted.iter().map(GObj::unroot).collect();
+        Some(self.list(&parts))
+    }
+
+    /// Check whether a string contains another.  Returns `None` if
+    /// either argument is not a string.
+    pub fn string_contains(&self, obj: impl GObj, needle: impl GObj) -> Option<bool> {
+        Some(self.as_str(obj)?.contains(self.as_str(needle)?))
+    }
+
+    /// Check whether a string starts with another.  Returns `None` if
+    /// either argument is not a string.
+    pub fn string_prefix(&self, obj: impl GObj, prefix: impl GObj) -> Option<bool> {
+        Some(self.as_str(obj)?.starts_with(self.as_str(prefix)?))
+    }
+
+    /// Check whether a string ends with another.  Returns `None` if
+    /// either argument is not a string.
+    pub fn string_suffix(&self, obj: impl GObj, suffix: impl GObj) -> Option<bool> {
+        Some(self.as_str(obj)?.ends_with(self.as_str(suffix)?))
+    }
+
+    /// Return a new string with all non-overlapping occurrences of one
+    /// string replaced by another.  Returns `None` if any argument is
+    /// not a string, or if the string to replace is empty.
+    pub fn string_replace(&mut self, obj: impl GObj, from: impl GObj, to: impl GObj) -> Option<Object> {
+        let from = self.as_str(from)?;
+        if from.is_empty() {
+            return None;
+        }
+        let s = self.as_str(obj)?.replace(from, self.as_str(to)?);
+        Some(self.string(s))
+    }
+
+    /// Return a new string with leading and trailing whitespace
+    /// removed, or `None` if the argument is not a string.
+    pub fn string_trim(&mut self, obj: impl GObj) -> Option<Object> {
+        let s = self.as_str(obj)?.trim().to_string();
+        Some(self.string(s))
+    }
+
+    /// Return a new string with leading whitespace removed, or `None`
+    /// if the argument is not a string.
+    pub fn string_trim_start(&mut self, obj: impl GObj) -> Option<Object> {
+        let s = self.as_str(obj)?.trim_start().to_string();
+        Some(self.string(s))
+    }
+
+    /// Return a new string with trailing whitespace removed, or `None`
+    /// if the argument is not a string.
+    pub fn string_trim_end(&mut self, obj: impl GObj) -> Option<Object> {
+        let s = self.as_str(obj)?.trim_end().to_string();
+        Some(self.string(s))
+    }
+}
+
+
 pub fn unescape(input: &str) -> Option<String> {
     let mut chars = input.chars();
     let mut output = String::new();
@@ -42,7 +200,7 @@ pub fn escape(input: &str) -> String {
                 output.push_str("\\^");
                 output.push(from_u32((c as u32) + ('a' as u32) - 1).unwrap());
             }
-            c @ '\x01'..='\x1f' => {
+            c @ '\x1c'..='\x1f' => {
                 output.push_str("\\^");
                 output.push(from_u32((c as u32) + ('A' as u32) - 1).unwrap());
             }
@@ -53,6 +211,42 @@ pub fn escape(input: &str) -> String {
     output
 }
 
+/// Escape a symbol name for writing between vertical bars.
+pub fn escape_symbol(input: &str) -> String {
+    escape(input).replace('|', "\\|")
+}
+
+/// Names of characters written as `#\name`.
+const CHAR_NAMES: [(char, &str); 9] = [
+    ('\0', "null"),
+    ('\x07', "alarm"),
+    ('\x08', "backspace"),
+    ('\t', "tab"),
+    ('\n', "newline"),
+    ('\r', "return"),
+    ('\x1b', "escape"),
+    (' ', "space"),
+    ('\x7f', "delete"),
+];
+
+/// Get the name of a character, if it has one.
+pub fn char_name(c: char) -> Option<&'static str> {
+    CHAR_NAMES.iter().find(|(ch, _)| *ch == c).map(|(_, name)| *name)
+}
+
+/// Parse the text after `#\` in a character literal: a single
+/// character, a character name or `x` followed by a hex code point.
+pub fn parse_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => CHAR_NAMES.iter().find(|(_, name)| *name == text).map(|(c, _)| *c).or_else(|| {
+            let hex = text.strip_prefix('x')?;
+            from_u32(u32::from_str_radix(hex, 16).ok()?)
+        }),
+    }
+}
+
 fn unescape_single(input: &mut Chars) -> Option<char> {
     match input.next() {
         Some('0') => Some('\x00'),
@@ -66,6 +260,7 @@ fn unescape_single(input: &mut Chars) -> Option<char> {
         Some('e') => Some('\x1b'),
         Some('"') => Some('\x22'),
         Some('\\') => Some('\x5c'),
+        Some('|') => Some('|'),
         Some('^') => {
             match input.next() {
                 Some('?') => Some('\x7f'),
@@ -84,9 +279,7 @@ fn unescape_unicode(input: &mut Chars, nchars: usize) -> Option<char> {
     let mut code: u32 = 0;
     for _ in 0..nchars {
         match input.next() {
-            Some(c) if ('0'..='9').contains(&c) ||
-                       ('a'..='f').contains(&c) ||
-                       ('A'..='F').contains(&c) =>
+            Some(c) if c.is_ascii_hexdigit() =>
                 code = code * 16 + c.to_digit(16).unwrap(),
             _ => return None,
         }
@@ -0,0 +1,31 @@
+use crate::{GObj, Object, VM};
+
+
+impl VM {
+    /// Attach a tag to a heap object, replacing any previous tag.  Tags
+    /// are user metadata kept beside the object, such as a class
+    /// identifier, and are dropped when the object is collected.  Stack
+    /// values cannot be tagged, and are ignored.
+    pub fn set_tag(&mut self, obj: impl GObj, tag: u32) {
+        if let Object::H(handle) = obj.unroot() {
+            self.tags.insert(handle, tag);
+        }
+    }
+
+    /// Get the tag of an object.  Returns `None` for untagged objects
+    /// and stack values.
+    pub fn get_tag(&self, obj: impl GObj) -> Option<u32> {
+        match obj.unroot() {
+            Object::H(handle) => self.tags.get(&handle).copied(),
+            Object::S(_) => None,
+        }
+    }
+
+    /// Remove the tag of an object, returning it.
+    pub fn remove_tag(&mut self, obj: impl GObj) -> Option<u32> {
+        match obj.unroot() {
+            Object::H(handle) => self.tags.remove(&handle),
+            Object::S(_) => None,
+        }
+    }
+}
@@ -1,3 +1,27 @@
+#[allow(clippy::clone_on_copy)]
 mod gc_count;
+mod natives;
 mod to_string;
 mod escaping;
+mod numbers;
+mod truthiness;
+mod equal;
+mod lists;
+mod vectors;
+mod procedures;
+mod env;
+mod eval;
+mod read;
+mod builder;
+mod copy;
+mod size;
+mod chars;
+mod strings;
+mod foreign;
+mod freeze;
+mod send;
+mod condition;
+mod values;
+mod access;
+mod promise;
+mod tag;
@@ -0,0 +1,48 @@
+use crate::*;
+
+#[test]
+fn with_heap_value() {
+    let mut vm = VM::new();
+    let list = vm.list(&[vm.int(1), vm.int(2)]);
+    let car = vm.with_heap_value(list, |val| match val {
+        HVal::Cons(car, _) => Some(*car),
+        _ => None,
+    });
+    assert_eq!(Some(Some(vm.int(1))), car);
+
+    let s = vm.string("abc".to_string());
+    assert_eq!(Some(3), vm.with_heap_value(s, |val| match val {
+        HVal::String(s) => s.len(),
+        _ => 0,
+    }));
+
+    assert_eq!(None, vm.with_heap_value(vm.int(1), |_| ()));
+    assert_eq!(None, vm.with_heap_value(Object::Nil, |_| ()));
+    vm.gc();
+    assert_eq!(None, vm.with_heap_value(s, |_| ()));
+}
+
+#[test]
+fn get_ref() {
+    let mut vm = VM::new();
+    let obj = vm.read("(#(1 \"a\") . 2/3)").unwrap();
+    let (vec, ratio) = match vm.get_ref(obj) {
+        Some(Ref::Cons(car, cdr)) => (car, cdr),
+        other => panic!("expected a cons, got {:?}", other),
+    };
+    let items = match vm.get_ref(vec) {
+        Some(Ref::Vec(items)) => items,
+        other => panic!("expected a vector, got {:?}", other),
+    };
+    assert_eq!(vm.int(1), items[0]);
+    assert!(matches!(vm.get_ref(items[1]), Some(Ref::String("a"))));
+    assert!(matches!(vm.get_ref(ratio), Some(Ref::Rational(2, 3))));
+
+    let err = vm.make_error("oops".to_string(), &[vm.int(1)]);
+    assert!(matches!(vm.get_ref(err), Some(Ref::Error { message: "oops", irritants: [_] })));
+    let env = vm.make_env(None);
+    assert!(matches!(vm.get_ref(env), Some(Ref::Env)));
+
+    assert!(vm.get_ref(vm.int(1)).is_none());
+    assert!(vm.get_ref(Object::Nil).is_none());
+}
@@ -0,0 +1,130 @@
+use crate::*;
+use super::natives::{make, pair};
+
+#[test]
+fn defaults() {
+    let mut vm = VmBuilder::new().build();
+    for _ in 0..100 {
+        vm.cons(Object::True, Object::Nil);
+    }
+    assert_eq!(100, vm.heapsize());
+}
+
+#[test]
+fn auto_gc() {
+    let mut vm = VmBuilder::new().gc_threshold(10).auto_gc(true).build();
+    let kept = vm.cons(Object::True, Object::Nil).root(&mut vm);
+    for _ in 0..9 {
+        vm.cons(Object::False, Object::Nil);
+    }
+    assert_eq!(10, vm.heapsize());
+
+    // The allocation crossing the threshold survives, with its contents.
+    let s = vm.string("x".to_string());
+    let pair = vm.cons(s, Object::Nil);
+    assert_eq!(3, vm.heapsize());
+    assert_eq!(Some(s), vm.car(pair));
+    assert_eq!(Some(Object::True), vm.car(kept.unroot()));
+
+    for _ in 0..100 {
+        vm.cons(Object::False, Object::Nil);
+    }
+    assert!(vm.heapsize() <= 11);
+}
+
+#[test]
+fn default() {
+    #[derive(Default)]
+    struct Interpreter {
+        vm: VM,
+    }
+
+    let mut interp = Interpreter::default();
+    assert_eq!(0, interp.vm.heapsize());
+    let a = interp.vm.symbol("a");
+    assert_eq!(Some("a"), interp.vm.symbol_name(a));
+    for _ in 0..100 {
+        interp.vm.cons(Object::True, Object::Nil);
+    }
+    assert_eq!(100, interp.vm.heapsize());
+}
+
+fn two(vm: &mut VM, _: &[Object]) -> Object {
+    let a = vm.string("a".to_string()).root(vm);
+    let b = vm.string("b".to_string());
+    vm.values(&[a.unroot(), b])
+}
+
+#[test]
+fn auto_gc_every_allocation() {
+    // With little live data, a collection runs every few allocations,
+    // so intermediate objects that are not kept alive show up as dead
+    let new_vm = || VmBuilder::new().gc_threshold(0).auto_gc(true).build();
+    let show = |vm: &mut VM, obj: Option<Object>| format!("{}", vm.wrap(obj.unwrap()));
+
+    let mut vm = new_vm();
+    let src = "(\"a\" \"b\" \"c\" \"d\" \"e\" \"f\" \"g\" \"h\" \"i\" \"j\")";
+    let list = vm.read(src).ok();
+    assert_eq!(src, show(&mut vm, list));
+
+    let src = "(\"a\" \"b\" \"a\" \"c\")";
+    let mut vm = new_vm();
+    let list = vm.read(src).ok();
+    let r = vm.take(list.unwrap(), 3);
+    assert_eq!("(\"a\" \"b\" \"a\")", show(&mut vm, r));
+    let mut vm = new_vm();
+    let list = vm.read(src).ok();
+    let r = vm.append(list.unwrap(), list.unwrap());
+    assert_eq!("(\"a\" \"b\" \"a\" \"c\" \"a\" \"b\" \"a\" \"c\")", show(&mut vm, r));
+    let mut vm = new_vm();
+    let list = vm.read(src).ok();
+    let r = vm.dedup_list(list.unwrap());
+    assert_eq!("(\"a\" \"b\" \"c\")", show(&mut vm, r));
+    let mut vm = new_vm();
+    let list = vm.read(src).ok();
+    let r = vm.sort_list(list.unwrap(), |vm, a, b| {
+        vm.string("garbage".to_string());
+        vm.as_str(a).cmp(&vm.as_str(b))
+    });
+    assert_eq!("(\"a\" \"a\" \"b\" \"c\")", show(&mut vm, r));
+
+    let mut vm = new_vm();
+    let nested = vm.read("((\"a\") (\"b\" \"c\") nil (\"d\"))").ok();
+    let r = vm.flatten_once(nested.unwrap());
+    assert_eq!("(\"a\" \"b\" \"c\" \"d\")", show(&mut vm, r));
+
+    let mut vm = new_vm();
+    let alist = vm.read("((a . \"1\") (b . \"2\") (c . \"3\"))").unwrap().root(&mut vm);
+    let (key, value) = (vm.symbol("b"), vm.string("x".to_string()));
+    let r = Some(vm.alist_set(alist.unroot(), key, value));
+    assert_eq!("((a . \"1\") (b . \"x\") (c . \"3\"))", show(&mut vm, r));
+
+    let mut vm = new_vm();
+    let s = vm.string("a,b,c,d".to_string()).root(&mut vm);
+    let sep = vm.string(",".to_string());
+    let r = vm.string_split(s.unroot(), sep);
+    assert_eq!("(\"a\" \"b\" \"c\" \"d\")", show(&mut vm, r));
+
+    let mut vm = new_vm();
+    let v = vm.read("#(1 2 3)").ok();
+    let r = vm.vec_map(v.unwrap(), |vm, _| vm.string("x".to_string()));
+    assert_eq!("#(\"x\" \"x\" \"x\")", show(&mut vm, r));
+
+    let mut vm = new_vm();
+    let (producer, consumer) = (vm.native(two).root(&mut vm), vm.native(pair));
+    let r = vm.call_with_values(producer.unroot(), consumer);
+    assert_eq!("(\"a\" . \"b\")", show(&mut vm, r));
+
+    for src in ["(pair (mk) (mk))", "((lambda (a) (pair a (mk))) (mk))"] {
+        let mut vm = new_vm();
+        let env = vm.make_env(None).root(&mut vm);
+        for (name, f) in [("mk", make as NativeFn), ("pair", pair)] {
+            let sym = vm.symbol(name);
+            let native = vm.native(f);
+            vm.env_define(env.unroot(), sym, native).unwrap();
+        }
+        let expr = vm.read(src).unwrap();
+        let r = vm.eval(expr, env.unroot()).ok();
+        assert_eq!("(\"x\" . \"x\")", show(&mut vm, r));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::*;
+
+#[test]
+fn conversions() {
+    let vm = VM::new();
+    assert_eq!(Some(vm.int(65)), vm.char_to_int(vm.char('A')));
+    assert_eq!(Some(vm.int(0x3bb)), vm.char_to_int(vm.char('λ')));
+    assert_eq!(Some(vm.char('λ')), vm.int_to_char(vm.int(0x3bb)));
+    assert_eq!(Some(vm.char('\0')), vm.int_to_char(vm.int(0)));
+    assert_eq!(None, vm.int_to_char(vm.int(0xd800)));
+    assert_eq!(None, vm.int_to_char(vm.int(0x110000)));
+    assert_eq!(None, vm.int_to_char(vm.int(-1)));
+    assert_eq!(None, vm.int_to_char(vm.float(65.0)));
+    assert_eq!(None, vm.char_to_int(vm.int(65)));
+}
+
+#[test]
+fn predicates() {
+    let vm = VM::new();
+    assert_eq!(Some(true), vm.char_alphabetic(vm.char('é')));
+    assert_eq!(Some(false), vm.char_alphabetic(vm.char('1')));
+    assert_eq!(Some(true), vm.char_numeric(vm.char('7')));
+    assert_eq!(Some(false), vm.char_numeric(vm.char('x')));
+    assert_eq!(Some(true), vm.char_whitespace(vm.char('\n')));
+    assert_eq!(Some(false), vm.char_whitespace(vm.char('_')));
+    assert_eq!(None, vm.char_alphabetic(Object::Nil));
+}
+
+#[test]
+fn case() {
+    let vm = VM::new();
+    assert_eq!(Some(vm.char('A')), vm.char_upcase(vm.char('a')));
+    assert_eq!(Some(vm.char('A')), vm.char_upcase(vm.char('A')));
+    assert_eq!(Some(vm.char('Λ')), vm.char_upcase(vm.char('λ')));
+    assert_eq!(Some(vm.char('1')), vm.char_upcase(vm.char('1')));
+    assert_eq!(Some(vm.char('S')), vm.char_upcase(vm.char('\u{df}')));
+    assert_eq!(Some(vm.char('é')), vm.char_downcase(vm.char('É')));
+    assert_eq!(Some(vm.char('i')), vm.char_downcase(vm.char('\u{130}')));
+    assert_eq!(Some(vm.char('-')), vm.char_downcase(vm.char('-')));
+    assert_eq!(None, vm.char_upcase(vm.int(97)));
+    assert_eq!(None, vm.char_downcase(Object::Nil));
+}
+
+#[test]
+fn read_print() {
+    let mut vm = VM::new();
+    for (src, c, printed) in [
+        ("#\\a", 'a', "#\\a"),
+        ("#\\(", '(', "#\\("),
+        ("#\\space", ' ', "#\\space"),
+        ("#\\newline", '\n', "#\\newline"),
+        ("#\\x3bb", 'λ', "#\\λ"),
+        ("#\\x1", '\x01', "#\\x1"),
+        ("#\\x", 'x', "#\\x"),
+    ] {
+        assert_eq!(Ok(vm.char(c)), vm.read(src), "{}", src);
+        assert_eq!(printed, format!("{}", vm.wrap(vm.char(c))));
+    }
+    let list = vm.read("(#\\a #\\) #\\b)").unwrap();
+    assert_eq!(Some(vec![vm.char('a'), vm.char(')'), vm.char('b')]), vm.list_to_vec(list));
+    assert_eq!("unknown character name", vm.read("#\\bogus").unwrap_err().message);
+    assert_eq!(Some(ReadOutcome::Incomplete), Reader::new("#\\").next_datum(&mut vm));
+    assert_ne!(vm.hash(vm.char('a')), vm.hash(vm.char('b')));
+}
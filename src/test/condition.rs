@@ -0,0 +1,45 @@
+use crate::*;
+
+#[test]
+fn eval_catch() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let expr = vm.read("(quote (1 2))").unwrap();
+    let value = vm.eval_catch(expr, env);
+    assert_eq!("(1 2)", format!("{}", vm.wrap(value)));
+    assert!(!vm.is_error(value));
+
+    let expr = vm.read("(f undefined)").unwrap();
+    let err = vm.eval_catch(expr, env);
+    assert!(vm.is_error(err));
+    assert_eq!("#<error: unbound variable: f>", format!("{}", vm.wrap(err)));
+    assert!(vm.debug_string(err).starts_with("#<error @"));
+
+    // Errors evaluate to themselves
+    assert_eq!(Ok(err), vm.eval(err, env));
+    let other = vm.eval_catch(expr, env);
+    assert!(!vm.equal(err, other));
+    assert!(!vm.is_error(vm.int(1)));
+}
+
+#[test]
+fn make_error() {
+    let mut vm = VM::new();
+    let s = vm.string("x".to_string());
+    let sym = vm.symbol("car");
+    let err = vm.make_error("not a pair".to_string(), &[sym, s, vm.int(1)]).root(&mut vm);
+    assert!(vm.is_error(err.unroot()));
+    assert_eq!(Some("not a pair"), vm.error_message(err.unroot()));
+    assert_eq!("#<error: not a pair (car \"x\" 1)>", format!("{}", vm.wrap(err.unroot())));
+
+    // Irritants are kept alive by the error
+    vm.gc();
+    let irritants = vm.error_irritants(err.unroot()).unwrap();
+    assert_eq!("(car \"x\" 1)", format!("{}", vm.wrap(irritants)));
+
+    let err = vm.make_error(String::new(), &[]);
+    assert_eq!("#<error: >", format!("{}", vm.wrap(err)));
+    assert_eq!(Some(Object::Nil), vm.error_irritants(err));
+    assert_eq!(None, vm.error_message(s));
+    assert_eq!(None, vm.error_irritants(vm.int(1)));
+}
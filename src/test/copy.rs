@@ -0,0 +1,130 @@
+use crate::*;
+
+#[test]
+fn snapshot() {
+    let mut vm = VM::new();
+    let sym = vm.symbol("sym");
+    let s = vm.string("text".to_string());
+    let shared = vm.list(&[vm.int(1), s]);
+    let vec = vm.vec_from_iter(vec![shared, sym, Object::Undef]);
+    vm.vec_set(vec, 2, vec).unwrap();
+    let list = vm.list(&[vec, shared, vm.float(2.5)]);
+
+    let (mut snap, roots) = vm.snapshot(&[list, sym]).unwrap();
+    let (copy, copy_sym) = (roots[0].unroot(), roots[1].unroot());
+    assert_eq!(copy_sym, sym);
+    assert_ne!(copy, list);
+    snap.gc();
+    assert_eq!(7, snap.heapsize());
+
+    let copy_vec = snap.car(copy).unwrap();
+    assert_eq!(Some(copy_vec), snap.vec_get(copy_vec, 2));
+    let copy_shared = snap.vec_get(copy_vec, 0).unwrap();
+    assert_eq!(Some(copy_shared), snap.list_to_vec(copy).map(|items| items[1]));
+    assert_eq!(Some("sym"), snap.symbol_name(snap.vec_get(copy_vec, 1).unwrap()));
+
+    snap.vec_set(copy_vec, 1, Object::Nil).unwrap();
+    assert_eq!(Some(sym), vm.vec_get(vec, 1));
+    drop(roots);
+    snap.gc();
+    assert_eq!(0, snap.heapsize());
+    assert_eq!(7, vm.heapsize());
+}
+
+#[test]
+fn snapshot_closure() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let src = "(define (f x) (if x (f #f) 'done))";
+    let def = vm.read(src).unwrap();
+    vm.eval(def, env).unwrap();
+
+    let (mut snap, roots) = vm.snapshot(&[env]).unwrap();
+    let call = snap.read("(f #t)").unwrap();
+    let done = snap.symbol("done");
+    assert_eq!(Ok(done), snap.eval(call, roots[0].unroot()));
+}
+
+#[test]
+fn import() {
+    let mut host = VM::new();
+    let mut sandbox = VM::new();
+    host.symbol("unrelated");
+    assert_eq!(Some(host.int(5)), host.import(&sandbox, sandbox.int(5)));
+
+    let obj = sandbox.read("(a #(1 \"two\" 3/4) . b)").unwrap();
+    let copy = host.import(&sandbox, obj).unwrap();
+    assert_eq!("(a #(1 \"two\" 3/4) . b)", format!("{}", host.wrap(copy)));
+    let a = host.symbol("a");
+    assert_eq!(Some(a), host.car(copy));
+    assert_ne!(sandbox.symbol("a"), a);
+    assert_eq!(5, host.heapsize());
+
+    let cycle = sandbox.vec_from_iter(vec![obj, Object::Nil]);
+    sandbox.vec_set(cycle, 1, cycle).unwrap();
+    let copy = host.import(&sandbox, cycle).unwrap();
+    assert_eq!(Some(copy), host.vec_get(copy, 1));
+    assert_eq!(11, host.heapsize());
+}
+
+#[test]
+fn copy_frozen() {
+    let mut vm = VM::new();
+    let table = vm.read("#((a . 1) (b . 2))").unwrap();
+    let entry = vm.vec_get(table, 0).unwrap();
+    vm.freeze(table);
+
+    let (mut snap, roots) = vm.snapshot(&[table]).unwrap();
+    let copy = roots[0].unroot();
+    let copy_entry = snap.vec_get(copy, 0).unwrap();
+    assert!(snap.is_frozen(copy));
+    assert!(!snap.is_frozen(copy_entry));
+    assert_eq!(Err(GinkgoError::Immutable), snap.vec_set(copy, 0, Object::Nil));
+    snap.set_cdr(copy_entry, snap.int(3)).unwrap();
+
+    let mut other = VM::new();
+    let copy = other.import(&vm, table).unwrap();
+    assert!(other.is_frozen(copy));
+    assert_eq!(Err(GinkgoError::Immutable), other.vec_set(copy, 0, Object::Nil));
+    assert!(!vm.is_frozen(entry));
+}
+
+#[test]
+fn copy_tags() {
+    let mut vm = VM::new();
+    let obj = vm.read("(a \"b\" #(c))").unwrap();
+    let items = vm.list_to_vec(obj).unwrap();
+    vm.set_tag(obj, 1);
+    vm.set_tag(items[1], 2);
+
+    let (snap, roots) = vm.snapshot(&[obj]).unwrap();
+    let copy = roots[0].unroot();
+    let items = snap.list_to_vec(copy).unwrap();
+    assert_eq!(Some(1), snap.get_tag(copy));
+    assert_eq!(Some(2), snap.get_tag(items[1]));
+    assert_eq!(None, snap.get_tag(items[2]));
+
+    let mut other = VM::new();
+    let copy = other.import(&vm, obj).unwrap();
+    let items = other.list_to_vec(copy).unwrap();
+    assert_eq!(Some(1), other.get_tag(copy));
+    assert_eq!(Some(2), other.get_tag(items[1]));
+}
+
+#[test]
+fn copy_dead() {
+    let mut vm = VM::new();
+    let dead = vm.string("dead".to_string());
+    vm.gc();
+    let s = vm.string("live".to_string());
+    let list = vm.list(&[s, vm.int(1), dead]);
+    let vec = vm.vec_from_iter(vec![list, s]);
+
+    let mut other = VM::new();
+    assert_eq!(None, other.import(&vm, dead));
+    assert_eq!(None, other.import(&vm, vec));
+    other.gc();
+    assert_eq!(0, other.heapsize());
+    assert!(vm.snapshot(&[s, list]).is_none());
+    assert!(vm.snapshot(&[s]).is_some());
+}
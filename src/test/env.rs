@@ -0,0 +1,61 @@
+use crate::*;
+
+#[test]
+fn symbols() {
+    let mut vm = VM::new();
+    let a = vm.symbol("abc");
+    let b = vm.symbol("abc");
+    let c = vm.symbol("def");
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!("abc", format!("{}", vm.wrap(a)));
+    assert_eq!(Some("def"), vm.symbol_name(c));
+    assert_eq!(None, vm.symbol_name(vm.int(1)));
+    assert_eq!(0, vm.heapsize());
+}
+
+#[test]
+fn env() {
+    let mut vm = VM::new();
+    let x = vm.symbol("x");
+    let y = vm.symbol("y");
+    let global = vm.make_env(None);
+    let local = vm.make_env(Some(global));
+    assert_eq!("#<environment>", format!("{}", vm.wrap(local)));
+
+    vm.env_define(global, x, vm.int(1)).unwrap();
+    vm.env_define(global, y, vm.int(2)).unwrap();
+    assert_eq!(Some(vm.int(1)), vm.env_lookup(local, x));
+
+    vm.env_define(local, x, vm.int(10)).unwrap();
+    assert_eq!(Some(vm.int(10)), vm.env_lookup(local, x));
+    assert_eq!(Some(vm.int(1)), vm.env_lookup(global, x));
+
+    vm.env_set(local, y, vm.int(20)).unwrap();
+    assert_eq!(Some(vm.int(20)), vm.env_lookup(global, y));
+    vm.env_set(local, x, vm.int(30)).unwrap();
+    assert_eq!(Some(vm.int(30)), vm.env_lookup(local, x));
+    assert_eq!(Some(vm.int(1)), vm.env_lookup(global, x));
+
+    let z = vm.symbol("z");
+    assert_eq!(None, vm.env_lookup(local, z));
+    assert_eq!(Err(GinkgoError::Unbound("z".to_string())), vm.env_set(local, z, Object::Nil));
+    assert_eq!(Err(GinkgoError::TypeMismatch("symbol")), vm.env_define(local, vm.int(1), Object::Nil));
+    assert_eq!(Err(GinkgoError::TypeMismatch("environment")), vm.env_define(Object::Nil, z, Object::Nil));
+    assert_eq!(None, vm.env_lookup(Object::Nil, x));
+}
+
+#[test]
+fn env_gc() {
+    let mut vm = VM::new();
+    let x = vm.symbol("x");
+    let global = vm.make_env(None);
+    let local = vm.make_env(Some(global));
+    let val = vm.cons(vm.int(1), Object::Nil);
+    vm.env_define(global, x, val).unwrap();
+
+    let local = local.root(&mut vm);
+    vm.gc();
+    assert_eq!(3, vm.heapsize());
+    assert_eq!("(1)", format!("{}", vm.wrap(vm.env_lookup(local.unroot(), x).unwrap())));
+}
@@ -0,0 +1,123 @@
+use crate::*;
+
+#[test]
+fn equal() {
+    let mut vm = VM::new();
+    assert!(vm.equal(Object::Nil, Object::Nil));
+    assert!(vm.equal(vm.int(1), vm.int(1)));
+    assert!(!vm.equal(vm.int(1), vm.float(1.0)));
+    assert!(vm.equal(vm.float(0.0), vm.float(-0.0)));
+    assert!(!vm.equal(Object::Nil, Object::False));
+
+    let a = vm.string("abc".to_string());
+    let b = vm.string("abc".to_string());
+    let c = vm.string("abd".to_string());
+    assert!(vm.equal(a, b));
+    assert!(!vm.equal(a, c));
+    assert_eq!(vm.hash(a), vm.hash(b));
+
+    let r = vm.rational(1, 3).unwrap();
+    let s = vm.rational(2, 6).unwrap();
+    assert!(vm.equal(r, s));
+    assert_eq!(vm.hash(r), vm.hash(s));
+
+    let l = vm.list(&[vm.int(1), a, r]);
+    let m = vm.list(&[vm.int(1), b, s]);
+    let n = vm.list(&[vm.int(1), b]);
+    assert!(vm.equal(l, m));
+    assert!(!vm.equal(l, n));
+    assert!(!vm.equal(l, a));
+    assert_eq!(vm.hash(l), vm.hash(m));
+
+    let v = vm.vec(2);
+    let w = vm.vec(2);
+    vm.vec_set(v, 0, l).unwrap();
+    vm.vec_set(w, 0, m).unwrap();
+    assert!(vm.equal(v, w));
+    assert_eq!(vm.hash(v), vm.hash(w));
+    vm.vec_set(w, 1, vm.int(0)).unwrap();
+    assert!(!vm.equal(v, w));
+}
+
+#[test]
+fn equal_cyclic_vectors() {
+    let mut vm = VM::new();
+    let v = vm.vec_from_iter(vec![Object::Nil, vm.int(1)]);
+    let w = vm.vec_from_iter(vec![Object::Nil, vm.int(1)]);
+    vm.vec_set(v, 0, v).unwrap();
+    vm.vec_set(w, 0, w).unwrap();
+    assert!(vm.equal(v, w));
+    assert!(vm.equal(v, v));
+    assert_eq!(Some(true), vm.vec_equal(v, w));
+    assert_eq!(vm.hash(v), vm.hash(w));
+
+    // A cycle through another vector has the same shape
+    let x = vm.vec_from_iter(vec![Object::Nil, vm.int(1)]);
+    let y = vm.vec_from_iter(vec![x, vm.int(1)]);
+    vm.vec_set(x, 0, y).unwrap();
+    assert!(vm.equal(v, x));
+
+    let z = vm.vec_from_iter(vec![Object::Nil, vm.int(2)]);
+    vm.vec_set(z, 0, z).unwrap();
+    assert!(!vm.equal(v, z));
+}
+
+#[test]
+fn equal_deep() {
+    let mut vm = VM::new();
+    let mut a = Object::Nil;
+    let mut b = Object::Nil;
+    for _ in 0..100_000 {
+        a = vm.cons(a, Object::Nil);
+        b = vm.cons(b, Object::Nil);
+    }
+    assert!(vm.equal(a, b));
+    assert_eq!(vm.hash(a), vm.hash(b));
+}
+
+#[test]
+fn compare() {
+    use std::cmp::Ordering::*;
+
+    let mut vm = VM::new();
+    let half = vm.rational(1, 2).unwrap();
+    assert_eq!(Some(Less), vm.compare(vm.int(1), vm.int(2)));
+    assert_eq!(Some(Equal), vm.compare(vm.int(2), vm.float(2.0)));
+    assert_eq!(Some(Greater), vm.compare(half, vm.float(0.25)));
+    assert_eq!(Some(Less), vm.compare(half, vm.int(1)));
+    assert_eq!(None, vm.compare(vm.float(f64::NAN), vm.float(f64::NAN)));
+    assert_eq!(None, vm.compare(vm.int(1), vm.float(f64::NAN)));
+
+    let a = vm.string("apple".to_string());
+    let b = vm.string("banana".to_string());
+    let c = vm.string("apple".to_string());
+    assert_eq!(Some(Less), vm.compare(a, b));
+    assert_eq!(Some(Equal), vm.compare(a, c));
+    assert_eq!(None, vm.compare(a, vm.int(1)));
+
+    let s = vm.symbol("a");
+    assert_eq!(None, vm.compare(s, s));
+    assert_eq!(None, vm.compare(Object::Nil, Object::Nil));
+}
+
+#[test]
+fn keyed() {
+    use std::collections::HashMap;
+
+    let mut vm = VM::new();
+    let a = vm.read("(1 \"two\" #(3))").unwrap();
+    let b = vm.read("(1 \"two\" #(3))").unwrap();
+    let c = vm.read("(1 \"two\" #(4))").unwrap();
+    let s = vm.symbol("s");
+
+    let mut map = HashMap::new();
+    map.insert(vm.keyed(a), 1);
+    map.insert(vm.keyed(s), 2);
+    map.insert(vm.keyed(b), 3);
+    assert_eq!(2, map.len());
+    assert_eq!(Some(&3), map.get(&vm.keyed(a)));
+    assert_eq!(None, map.get(&vm.keyed(c)));
+    assert_eq!(Some(&2), map.get(&vm.keyed(s)));
+    assert_eq!(None, map.get(&vm.keyed(vm.float(f64::NAN))));
+    assert_eq!(a, map.keys().find(|k| k.object() != s).unwrap().object());
+}
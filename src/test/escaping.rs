@@ -1,4 +1,5 @@
 use crate::string::*;
+use proptest::prelude::*;
 
 #[test]
 fn unescape_() {
@@ -14,6 +15,7 @@ fn unescape_() {
     assert_eq!(unescape("\\e"), Some("\x1b".to_string()));
     assert_eq!(unescape("\\\""), Some("\"".to_string()));
     assert_eq!(unescape("\\\\"), Some("\\".to_string()));
+    assert_eq!(unescape("\\|"), Some("|".to_string()));
     
     assert_eq!(unescape("\\^@"), Some("\0".to_string()));
     assert_eq!(unescape("\\^A"), Some("\x01".to_string()));
@@ -21,6 +23,9 @@ fn unescape_() {
     assert_eq!(unescape("\\^Z"), Some("\x1a".to_string()));
     assert_eq!(unescape("\\^z"), Some("\x1a".to_string()));
     assert_eq!(unescape("\\^["), Some("\x1b".to_string()));
+    assert_eq!(unescape("\\^\\"), Some("\x1c".to_string()));
+    assert_eq!(unescape("\\^]"), Some("\x1d".to_string()));
+    assert_eq!(unescape("\\^^"), Some("\x1e".to_string()));
     assert_eq!(unescape("\\^_"), Some("\x1f".to_string()));
     assert_eq!(unescape("\\^?"), Some("\x7f".to_string()));
     
@@ -50,6 +55,9 @@ fn escape_() {
     
     assert_eq!(escape("\x01"), "\\^a".to_string());
     assert_eq!(escape("\x1a"), "\\^z".to_string());
+    assert_eq!(escape("\x1c"), "\\^\\".to_string());
+    assert_eq!(escape("\x1d"), "\\^]".to_string());
+    assert_eq!(escape("\x1e"), "\\^^".to_string());
     assert_eq!(escape("\x1f"), "\\^_".to_string());
     assert_eq!(escape("\x7f"), "\\^?".to_string());
     
@@ -58,3 +66,15 @@ fn escape_() {
         "\\a\\b\\t\\n\\v\\f\\r\\^t\\^_\\\"\\\\\\^?\\e",
     );
 }
+
+proptest! {
+    #[test]
+    fn round_trip(s in any::<String>()) {
+        prop_assert_eq!(unescape(&escape(&s)), Some(s));
+    }
+
+    #[test]
+    fn round_trip_control(s in "[\\x00-\\x1f\\x7f\"\\\\a-z]*") {
+        prop_assert_eq!(unescape(&escape(&s)), Some(s));
+    }
+}
@@ -0,0 +1,337 @@
+use crate::*;
+use super::natives::{add, sub, is_zero, make, pair};
+
+#[test]
+fn atoms() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    assert_eq!(Ok(vm.int(1)), vm.eval(vm.int(1), env));
+    assert_eq!(Ok(vm.float(1.5)), vm.eval(vm.float(1.5), env));
+    assert_eq!(Ok(Object::True), vm.eval(Object::True, env));
+    assert_eq!(Ok(Object::False), vm.eval(Object::False, env));
+    assert_eq!(Ok(Object::Nil), vm.eval(Object::Nil, env));
+    let s = vm.string("abc".to_string());
+    assert_eq!(Ok(s), vm.eval(s, env));
+    let v = vm.vec(1);
+    assert_eq!(Ok(v), vm.eval(v, env));
+}
+
+#[test]
+fn variables() {
+    let mut vm = VM::new();
+    let x = vm.symbol("x");
+    let y = vm.symbol("y");
+    let global = vm.make_env(None);
+    let local = vm.make_env(Some(global));
+    vm.env_define(global, x, vm.int(1)).unwrap();
+    vm.env_define(local, y, vm.int(2)).unwrap();
+
+    assert_eq!(Ok(vm.int(1)), vm.eval(x, local));
+    assert_eq!(Ok(vm.int(2)), vm.eval(y, local));
+    assert_eq!(Err(GinkgoError::Unbound("y".to_string())), vm.eval(y, global));
+}
+
+#[test]
+fn quote() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let quote = vm.symbol("quote");
+    let x = vm.symbol("x");
+
+    let expr = vm.list(&[quote, x]);
+    assert_eq!(Ok(x), vm.eval(expr, env));
+
+    let datum = vm.list(&[vm.int(1), x]);
+    let expr = vm.list(&[quote, datum]);
+    assert_eq!(Ok(datum), vm.eval(expr, env));
+
+    let expr = vm.list(&[quote]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+    let expr = vm.list(&[quote, x, x]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+    let expr = vm.cons(quote, x);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+}
+
+#[test]
+fn if_() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let if_ = vm.symbol("if");
+    let x = vm.symbol("x");
+    let unbound = vm.symbol("unbound");
+    vm.env_define(env, x, Object::False).unwrap();
+
+    let expr = vm.list(&[if_, Object::True, vm.int(1), vm.int(2)]);
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+    let expr = vm.list(&[if_, Object::False, vm.int(1), vm.int(2)]);
+    assert_eq!(Ok(vm.int(2)), vm.eval(expr, env));
+    let expr = vm.list(&[if_, x, unbound, vm.int(2)]);
+    assert_eq!(Ok(vm.int(2)), vm.eval(expr, env));
+    let expr = vm.list(&[if_, Object::Nil, vm.int(1), unbound]);
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+    let expr = vm.list(&[if_, vm.int(0), vm.int(1), vm.int(2)]);
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+
+    let expr = vm.list(&[if_, Object::True, vm.int(1)]);
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+    let expr = vm.list(&[if_, Object::False, vm.int(1)]);
+    assert_eq!(Ok(Object::Undef), vm.eval(expr, env));
+
+    let expr = vm.list(&[if_, unbound, vm.int(1)]);
+    assert_eq!(Err(GinkgoError::Unbound("unbound".to_string())), vm.eval(expr, env));
+    let expr = vm.list(&[if_, Object::True]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+    let expr = vm.list(&[if_, Object::True, vm.int(1), vm.int(2), vm.int(3)]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+}
+
+#[test]
+fn lambda() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let lambda = vm.symbol("lambda");
+    let plus = vm.symbol("+");
+    let x = vm.symbol("x");
+    let y = vm.symbol("y");
+    let native = vm.native(add);
+    vm.env_define(env, plus, native).unwrap();
+
+    // ((lambda (x y) (+ x y)) 1 2)
+    let params = vm.list(&[x, y]);
+    let body = vm.list(&[plus, x, y]);
+    let expr = vm.list(&[lambda, params, body]);
+    let closure = vm.eval(expr, env).unwrap();
+    assert_eq!("#<procedure>", format!("{}", vm.wrap(closure)));
+    let call = vm.list(&[expr, vm.int(1), vm.int(2)]);
+    assert_eq!(Ok(vm.int(3)), vm.eval(call, env));
+    assert_eq!(Some(vm.int(7)), vm.apply(closure, &[vm.int(3), vm.int(4)]));
+
+    // Closures capture their defining environment
+    // (((lambda (x) (lambda (y) (+ x y))) 10) 5)
+    let inner_params = vm.list(&[y]);
+    let inner = vm.list(&[lambda, inner_params, body]);
+    let outer_params = vm.list(&[x]);
+    let outer = vm.list(&[lambda, outer_params, inner]);
+    let make = vm.list(&[outer, vm.int(10)]);
+    let call = vm.list(&[make, vm.int(5)]);
+    assert_eq!(Ok(vm.int(15)), vm.eval(call, env));
+
+    // The body is evaluated in order, returning the last value
+    let body = vm.list(&[lambda, Object::Nil, vm.int(1), vm.int(2)]);
+    let call = vm.list(&[body]);
+    assert_eq!(Ok(vm.int(2)), vm.eval(call, env));
+
+    let call = vm.list(&[expr, vm.int(1)]);
+    assert_eq!(Err(GinkgoError::Arity { expected: 2, given: 1 }), vm.eval(call, env));
+    let call = vm.list(&[vm.int(1), vm.int(1)]);
+    assert_eq!(Err(GinkgoError::TypeMismatch("procedure")), vm.eval(call, env));
+    let bad = vm.list(&[lambda, params]);
+    assert!(matches!(vm.eval(bad, env), Err(GinkgoError::Syntax(_))));
+    let bad_params = vm.list(&[vm.int(1)]);
+    let bad = vm.list(&[lambda, bad_params, x]);
+    assert!(matches!(vm.eval(bad, env), Err(GinkgoError::Syntax(_))));
+}
+
+#[test]
+fn mutated_lambda_body() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let f = vm.symbol("f");
+    let malformed = Err(GinkgoError::Syntax("malformed lambda body".to_string()));
+
+    // (lambda () 1 2), with the body then made improper or empty
+    let lambda = vm.symbol("lambda");
+    let expr = vm.list(&[lambda, Object::Nil, vm.int(1), vm.int(2)]);
+    let closure = vm.eval(expr, env).unwrap();
+    vm.env_define(env, f, closure).unwrap();
+    let call = vm.list(&[f]);
+    let params = vm.cdr(expr).unwrap();
+    let body = vm.cdr(params).unwrap();
+    vm.set_cdr(body, vm.int(5)).unwrap();
+    assert_eq!(malformed, vm.eval(call, env));
+    assert_eq!(None, vm.apply(closure, &[]));
+
+    vm.set_cdr(params, Object::Nil).unwrap();
+    assert_eq!(malformed, vm.eval(call, env));
+}
+
+#[test]
+fn closure_gc() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let lambda = vm.symbol("lambda");
+    let x = vm.symbol("x");
+    let params = vm.list(&[]);
+    let body = vm.list(&[lambda, params, x]);
+    let captured = vm.make_env(Some(env));
+    let value = vm.string("captured".to_string());
+    vm.env_define(captured, x, value).unwrap();
+    let closure = vm.eval(body, captured).unwrap().root(&mut vm);
+    let env = env.root(&mut vm);
+    vm.gc();
+
+    let result = vm.apply(closure.unroot(), &[]).unwrap();
+    assert_eq!("\"captured\"", format!("{}", vm.wrap(result)));
+    drop(closure);
+    drop(env);
+    vm.gc();
+    assert_eq!(0, vm.heapsize());
+}
+
+#[test]
+fn define_set() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let define = vm.symbol("define");
+    let set = vm.symbol("set!");
+    let plus = vm.symbol("+");
+    let x = vm.symbol("x");
+    let y = vm.symbol("y");
+    let f = vm.symbol("f");
+    let native = vm.native(add);
+    vm.env_define(env, plus, native).unwrap();
+
+    let expr = vm.list(&[define, x, vm.int(1)]);
+    assert_eq!(Ok(Object::Undef), vm.eval(expr, env));
+    assert_eq!(Some(vm.int(1)), vm.env_lookup(env, x));
+
+    let sum = vm.list(&[plus, x, vm.int(1)]);
+    let expr = vm.list(&[set, x, sum]);
+    assert_eq!(Ok(Object::Undef), vm.eval(expr, env));
+    assert_eq!(Some(vm.int(2)), vm.env_lookup(env, x));
+
+    // (define (f y) (+ x y))
+    let signature = vm.list(&[f, y]);
+    let body = vm.list(&[plus, x, y]);
+    let expr = vm.list(&[define, signature, body]);
+    assert_eq!(Ok(Object::Undef), vm.eval(expr, env));
+    let call = vm.list(&[f, vm.int(5)]);
+    assert_eq!(Ok(vm.int(7)), vm.eval(call, env));
+
+    let expr = vm.list(&[set, y, vm.int(1)]);
+    assert_eq!(Err(GinkgoError::Unbound("y".to_string())), vm.eval(expr, env));
+    let expr = vm.list(&[define, x]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+    let expr = vm.list(&[define, vm.int(1), vm.int(2)]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+    let expr = vm.list(&[set, vm.int(1), vm.int(2)]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+    let expr = vm.list(&[define, signature]);
+    assert!(matches!(vm.eval(expr, env), Err(GinkgoError::Syntax(_))));
+}
+
+#[test]
+fn begin() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let begin = vm.symbol("begin");
+    let define = vm.symbol("define");
+    let set = vm.symbol("set!");
+    let x = vm.symbol("x");
+    let unbound = vm.symbol("unbound");
+
+    let expr = vm.list(&[begin]);
+    assert_eq!(Ok(Object::Undef), vm.eval(expr, env));
+    let expr = vm.list(&[begin, vm.int(1), vm.int(2), vm.int(3)]);
+    assert_eq!(Ok(vm.int(3)), vm.eval(expr, env));
+
+    let def = vm.list(&[define, x, vm.int(1)]);
+    let assign = vm.list(&[set, x, vm.int(2)]);
+    let expr = vm.list(&[begin, def, assign, x]);
+    assert_eq!(Ok(vm.int(2)), vm.eval(expr, env));
+
+    let expr = vm.list(&[begin, unbound, vm.int(1)]);
+    assert_eq!(Err(GinkgoError::Unbound("unbound".to_string())), vm.eval(expr, env));
+}
+
+#[test]
+fn tail_calls() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let define = vm.symbol("define");
+    let if_ = vm.symbol("if");
+    let begin = vm.symbol("begin");
+    let minus = vm.symbol("-");
+    let zero = vm.symbol("zero?");
+    let count = vm.symbol("count");
+    let n = vm.symbol("n");
+    let native = vm.native(sub);
+    vm.env_define(env, minus, native).unwrap();
+    let native = vm.native(is_zero);
+    vm.env_define(env, zero, native).unwrap();
+
+    // (define (count n) (if (zero? n) 'done (begin n (count (- n 1)))))
+    let signature = vm.list(&[count, n]);
+    let test = vm.list(&[zero, n]);
+    let decrement = vm.list(&[minus, n, vm.int(1)]);
+    let recurse = vm.list(&[count, decrement]);
+    let step = vm.list(&[begin, n, recurse]);
+    let done = vm.string("done".to_string());
+    let body = vm.list(&[if_, test, done, step]);
+    let def = vm.list(&[define, signature, body]);
+    vm.eval(def, env).unwrap();
+
+    let call = vm.list(&[count, vm.int(1_000_000)]);
+    assert_eq!(Ok(done), vm.eval(call, env));
+}
+
+#[test]
+fn rest_parameters() {
+    let mut vm = VmBuilder::new().gc_threshold(0).build();
+    let env = vm.make_env(None).root(&mut vm);
+    let eval = |vm: &mut VM, src: &str| {
+        let expr = vm.read(src).unwrap().root(vm);
+        vm.eval(expr.unroot(), env.unroot()).map(|obj| format!("{}", vm.wrap(obj)))
+    };
+
+    eval(&mut vm, "(define (f a b . rest) (quote ignored) rest)").unwrap();
+    eval(&mut vm, "(define list (lambda args args))").unwrap();
+    assert_eq!(Ok("(3 4 5)".to_string()), eval(&mut vm, "(f 1 2 3 4 5)"));
+    assert_eq!(Ok("nil".to_string()), eval(&mut vm, "(f 1 2)"));
+    assert_eq!(Ok("(1 \"x\" (2))".to_string()), eval(&mut vm, "(list 1 \"x\" (list 2))"));
+    assert_eq!(Ok("nil".to_string()), eval(&mut vm, "(list)"));
+
+    // The frame survives collections triggered by the rest list
+    eval(&mut vm, "(define (first a . rest) a)").unwrap();
+    let first = vm.symbol("first");
+    let first = vm.eval(first, env.unroot()).unwrap();
+    vm.auto_gc = true;
+    for i in 0..100 {
+        let args: Vec<_> = (0..i % 7 + 1).map(|i| vm.int(i)).collect();
+        assert_eq!(Some(vm.int(0)), vm.apply(first, &args));
+    }
+    vm.auto_gc = false;
+
+    assert_eq!(Err(GinkgoError::ArityAtLeast { expected: 2, given: 1 }), eval(&mut vm, "(f 1)"));
+    assert_eq!("expected at least 2 arguments, got 1", format!("{}", GinkgoError::ArityAtLeast { expected: 2, given: 1 }));
+    assert!(matches!(eval(&mut vm, "(lambda (a . 1) a)"), Err(GinkgoError::Syntax(_))));
+    assert!(matches!(eval(&mut vm, "(lambda 1 a)"), Err(GinkgoError::Syntax(_))));
+}
+
+#[test]
+fn gc_during_eval() {
+    let mut vm = VmBuilder::new().gc_threshold(0).build();
+    let env = vm.make_env(None).root(&mut vm);
+    let natives: [(&str, NativeFn); 4] = [("mk", make), ("pair", pair), ("-", sub), ("zero?", is_zero)];
+    for (name, f) in natives {
+        let sym = vm.symbol(name);
+        let native = vm.native(f);
+        vm.env_define(env.unroot(), sym, native).unwrap();
+    }
+
+    // Every allocation collects garbage while evaluating
+    let eval = |vm: &mut VM, src: &str| {
+        let expr = vm.read(src).unwrap();
+        vm.auto_gc = true;
+        let result = vm.eval(expr, env.unroot()).map(|obj| format!("{}", vm.wrap(obj)));
+        vm.auto_gc = false;
+        result
+    };
+
+    assert_eq!(Ok("(\"x\" . \"x\")".to_string()), eval(&mut vm, "(pair (mk) (mk))"));
+    assert_eq!(Ok("(\"x\" . \"x\")".to_string()), eval(&mut vm, "((lambda (a) (pair a (mk))) (mk))"));
+    eval(&mut vm, "(define (build n acc) (if (zero? n) acc (build (- n 1) (pair (mk) acc))))").unwrap();
+    assert_eq!(Ok("(\"x\" \"x\" \"x\")".to_string()), eval(&mut vm, "(build 3 nil)"));
+    assert_eq!(Ok("(\"x\" . 1)".to_string()), eval(&mut vm, "((lambda (a . rest) (pair (mk) a)) 1 (mk) (mk))"));
+}
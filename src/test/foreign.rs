@@ -0,0 +1,37 @@
+use crate::*;
+
+#[test]
+fn foreign() {
+    let mut vm = VM::new();
+    let a = vm.foreign(Box::new(String::from("handle")));
+    let b = vm.foreign(Box::new(String::from("handle")));
+    assert_eq!(Some(&String::from("handle")), vm.foreign_ref::<String>(a));
+    assert_eq!(None, vm.foreign_ref::<i32>(a));
+    assert_eq!(None, vm.foreign_ref::<String>(vm.int(1)));
+    assert_eq!("#<foreign>", format!("{}", vm.wrap(a)));
+
+    assert!(vm.equal(a, a));
+    assert!(!vm.equal(a, b));
+    let l = vm.list(&[a]);
+    let m = vm.list(&[a]);
+    assert!(vm.equal(l, m));
+
+    let mut other = VM::new();
+    let copy = other.import(&vm, a).unwrap();
+    assert_eq!(Some(&String::from("handle")), other.foreign_ref::<String>(copy));
+}
+
+#[test]
+fn foreign_gc() {
+    use std::rc::Rc;
+
+    let mut vm = VM::new();
+    let token = Rc::new(());
+    let obj = vm.foreign(Box::new(token.clone()));
+    let rooted = vm.list(&[obj]).root(&mut vm);
+    vm.gc();
+    assert_eq!(2, Rc::strong_count(&token));
+    drop(rooted);
+    vm.gc();
+    assert_eq!(1, Rc::strong_count(&token));
+}
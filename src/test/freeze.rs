@@ -0,0 +1,66 @@
+use crate::*;
+
+#[test]
+fn freeze() {
+    let mut vm = VM::new();
+    let v = vm.vec_from_iter(vec![vm.int(2), vm.int(1)]);
+    let c = vm.cons(vm.int(1), v);
+    vm.freeze(c);
+    vm.freeze(vm.int(1));
+    assert!(vm.is_frozen(c));
+    assert!(!vm.is_frozen(v));
+    assert!(!vm.is_frozen(vm.int(1)));
+
+    assert_eq!(Err(GinkgoError::Immutable), vm.set_car(c, Object::Nil));
+    assert_eq!(Err(GinkgoError::Immutable), vm.set_cdr(c, Object::Nil));
+    assert_eq!(Some(vm.int(1)), vm.car(c));
+    vm.vec_set(v, 0, vm.int(3)).unwrap();
+
+    vm.freeze(v);
+    assert_eq!(Err(GinkgoError::Immutable), vm.vec_set(v, 0, vm.int(4)));
+    assert_eq!(Err(GinkgoError::Immutable), vm.vec_sort(v, |vm, a, b| vm.compare(a, b).unwrap()));
+    assert_eq!(Some(vec![vm.int(3), vm.int(1)]), vm.vec_to_rust(v));
+    assert_eq!("cannot mutate a frozen object", format!("{}", GinkgoError::Immutable));
+}
+
+#[test]
+fn freeze_env() {
+    let mut vm = VM::new();
+    let x = vm.symbol("x");
+    let global = vm.make_env(None);
+    vm.env_define(global, x, vm.int(1)).unwrap();
+    let local = vm.make_env(Some(global));
+    vm.freeze(global);
+
+    assert_eq!(Err(GinkgoError::Immutable), vm.env_define(global, x, vm.int(2)));
+    assert_eq!(Err(GinkgoError::Immutable), vm.env_set(local, x, vm.int(2)));
+    vm.env_define(local, x, vm.int(3)).unwrap();
+    vm.env_set(local, x, vm.int(4)).unwrap();
+    assert_eq!(Some(vm.int(1)), vm.env_lookup(global, x));
+    assert_eq!(Some(vm.int(4)), vm.env_lookup(local, x));
+}
+
+#[test]
+fn mutators() {
+    let mut vm = VM::new();
+    let c = vm.cons(vm.int(1), vm.int(2));
+    vm.set_car(c, vm.int(3)).unwrap();
+    vm.set_cdr(c, c).unwrap();
+    assert_eq!(Some(vm.int(3)), vm.car(c));
+    assert_eq!(Some(c), vm.cdr(c));
+    assert_eq!(Err(GinkgoError::TypeMismatch("cons")), vm.set_car(Object::Nil, c));
+
+    let v = vm.vec(1);
+    assert_eq!(Err(GinkgoError::IndexOutOfBounds { index: 1, len: 1, kind: "vector" }), vm.vec_set(v, 1, c));
+    assert_eq!(Err(GinkgoError::TypeMismatch("vector")), vm.vec_set(c, 0, c));
+}
+
+#[test]
+fn freeze_gc() {
+    let mut vm = VM::new();
+    let c = vm.cons(Object::Nil, Object::Nil);
+    vm.freeze(c);
+    vm.gc();
+    let d = vm.cons(Object::Nil, Object::Nil);
+    assert!(!vm.is_frozen(d));
+}
@@ -36,3 +36,56 @@ fn count_rooted() {
     vm.gc();
     assert_eq!(0, vm.heapsize());
 }
+
+#[test]
+fn alloc_hook() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut vm = VM::new();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = seen.clone();
+    vm.set_alloc_hook(move |val| log.borrow_mut().push(match val {
+        HVal::Cons(..) => "cons",
+        HVal::Vec(_) => "vec",
+        HVal::String(_) => "string",
+        _ => "other",
+    }));
+
+    let a = vm.string("a".to_string());
+    let b = vm.cons(a, Object::Nil);
+    vm.vec(3);
+    vm.int(1);
+    vm.car(b);
+    vm.rational(1, 2);
+    assert_eq!(vec!["string", "cons", "vec", "other"], *seen.borrow());
+
+    vm.remove_alloc_hook();
+    vm.cons(Object::Nil, Object::Nil);
+    assert_eq!(4, seen.borrow().len());
+}
+
+#[test]
+fn weak() {
+    let mut vm = VM::new();
+    let kept = vm.cons(Object::True, Object::Nil);
+    let lost = vm.cons(Object::False, Object::Nil);
+    let wk = vm.weak(kept);
+    let wl = vm.weak(lost);
+    let wi = vm.weak(vm.int(3));
+    assert_eq!(2, vm.heapsize());
+
+    let rooted = kept.root(&mut vm);
+    assert_eq!(Some(lost), vm.upgrade(&wl));
+    vm.gc();
+    assert_eq!(1, vm.heapsize());
+    assert_eq!(Some(kept), vm.upgrade(&wk));
+    assert_eq!(None, vm.upgrade(&wl));
+    assert_eq!(Some(vm.int(3)), vm.upgrade(&wi));
+
+    drop(rooted);
+    vm.gc();
+    assert_eq!(None, vm.upgrade(&wk));
+    vm.cons(Object::Nil, Object::Nil);
+    assert_eq!(None, vm.upgrade(&wk));
+}
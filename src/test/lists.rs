@@ -0,0 +1,379 @@
+use crate::*;
+
+#[test]
+fn dedup() {
+    let mut vm = VM::new();
+    let a = vm.string("a".to_string());
+    let b = vm.string("a".to_string());
+    let l = vm.list(&[vm.int(1), a, vm.int(2), vm.int(1), b, vm.float(1.0)]);
+    let r = vm.dedup_list(l).unwrap();
+    assert_eq!("(1 \"a\" 2 1.0)", format!("{}", vm.wrap(r)));
+    assert_eq!(Some(a), vm.car(vm.cdr(r).unwrap()));
+
+    assert_eq!(Some(Object::Nil), vm.dedup_list(Object::Nil));
+
+    let items: Vec<_> = (0..10_000).map(|i| vm.int(i % 100)).collect();
+    let l = vm.list(&items);
+    let r = vm.dedup_list(l).unwrap();
+    let expected: Vec<_> = (0..100).map(|i| vm.int(i)).collect();
+    let expected = vm.list(&expected);
+    assert!(vm.equal(r, expected));
+
+    let l = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(None, vm.dedup_list(l));
+    assert_eq!(None, vm.dedup_list(vm.int(2)));
+}
+
+#[test]
+fn sort() {
+    let mut vm = VM::new();
+    let half = vm.rational(1, 2).unwrap();
+    let l = vm.list(&[vm.int(3), vm.float(-1.5), half, vm.int(0), vm.int(1)]);
+    let r = vm.sort_list_numeric(l).unwrap();
+    assert_eq!("(-1.5 0 1/2 1 3)", format!("{}", vm.wrap(r)));
+    assert_eq!("(3 -1.5 1/2 0 1)", format!("{}", vm.wrap(l)));
+
+    let pairs: Vec<_> = [(2, 0), (1, 1), (2, 2), (1, 3)].iter()
+        .map(|&(k, v)| vm.cons(vm.int(k), vm.int(v)))
+        .collect();
+    let l = vm.list(&pairs);
+    let r = vm.sort_list(l, |vm, a, b| {
+        let a = vm.car(a).unwrap().as_int().unwrap();
+        let b = vm.car(b).unwrap().as_int().unwrap();
+        a.cmp(&b)
+    }).unwrap();
+    assert_eq!("((1 . 1) (1 . 3) (2 . 0) (2 . 2))", format!("{}", vm.wrap(r)));
+
+    assert_eq!(Some(Object::Nil), vm.sort_list_numeric(Object::Nil));
+    let l = vm.list(&[vm.int(1), Object::Nil]);
+    assert_eq!(None, vm.sort_list_numeric(l));
+    let l = vm.list(&[vm.int(1), vm.float(f64::NAN)]);
+    assert_eq!(None, vm.sort_list_numeric(l));
+    let l = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(None, vm.sort_list(l, |_, _, _| std::cmp::Ordering::Equal));
+}
+
+#[test]
+fn append() {
+    let mut vm = VM::new();
+    let a = vm.list(&[vm.int(1), vm.int(2)]);
+    let b = vm.list(&[vm.int(3)]);
+    let r = vm.append(a, b).unwrap();
+    assert_eq!("(1 2 3)", format!("{}", vm.wrap(r)));
+    assert_eq!(Some(b), vm.cdr(vm.cdr(r).unwrap()));
+    assert_eq!(Some(b), vm.append(Object::Nil, b));
+    let r = vm.append(a, vm.int(4)).unwrap();
+    assert_eq!("(1 2 . 4)", format!("{}", vm.wrap(r)));
+    let c = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(None, vm.append(c, b));
+}
+
+#[test]
+fn flatten_once() {
+    let mut vm = VM::new();
+    let a = vm.list(&[vm.int(1), vm.int(2)]);
+    let b = vm.list(&[vm.int(3)]);
+    let c = vm.list(&[b, vm.int(4)]);
+    let l = vm.list(&[a, Object::Nil, c, b]);
+    let r = vm.flatten_once(l).unwrap();
+    assert_eq!("(1 2 (3) 4 3)", format!("{}", vm.wrap(r)));
+    assert_eq!("((1 2) nil ((3) 4) (3))", format!("{}", vm.wrap(l)));
+
+    assert_eq!(Some(Object::Nil), vm.flatten_once(Object::Nil));
+    let l = vm.list(&[Object::Nil, Object::Nil]);
+    assert_eq!(Some(Object::Nil), vm.flatten_once(l));
+
+    let l = vm.list(&[a, vm.int(5)]);
+    assert_eq!(None, vm.flatten_once(l));
+    let l = vm.list(&[vm.int(5), a]);
+    assert_eq!(None, vm.flatten_once(l));
+    let l = vm.cons(a, b);
+    assert_eq!(None, vm.flatten_once(l));
+}
+
+#[test]
+fn take_drop() {
+    let mut vm = VM::new();
+    let l = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+
+    let r = vm.take(l, 2).unwrap();
+    assert_eq!("(1 2)", format!("{}", vm.wrap(r)));
+    let r = vm.take(l, 5).unwrap();
+    assert_eq!("(1 2 3)", format!("{}", vm.wrap(r)));
+    assert_ne!(l, r);
+    assert_eq!(Some(Object::Nil), vm.take(l, 0));
+    assert_eq!(Some(Object::Nil), vm.take(Object::Nil, 3));
+
+    let r = vm.drop(l, 1).unwrap();
+    assert_eq!("(2 3)", format!("{}", vm.wrap(r)));
+    assert_eq!(vm.cdr(l), Some(r));
+    assert_eq!(Some(l), vm.drop(l, 0));
+    assert_eq!(Some(Object::Nil), vm.drop(l, 3));
+    assert_eq!(Some(Object::Nil), vm.drop(l, 10));
+
+    let c = vm.cons(vm.int(1), vm.int(2));
+    let r = vm.take(c, 1).unwrap();
+    assert_eq!("(1)", format!("{}", vm.wrap(r)));
+    assert_eq!(None, vm.take(c, 2));
+    assert_eq!(Some(vm.int(2)), vm.drop(c, 1));
+    assert_eq!(None, vm.drop(c, 2));
+}
+
+#[test]
+fn list_tail() {
+    let mut vm = VM::new();
+    let l = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+    assert_eq!(Some(l), vm.list_tail(l, 0));
+    assert_eq!(vm.cdr(l), vm.list_tail(l, 1));
+    assert_eq!(Some(Object::Nil), vm.list_tail(l, 3));
+    assert_eq!(None, vm.list_tail(l, 4));
+    assert_eq!(Some(Object::Nil), vm.drop(l, 4));
+    assert_eq!(Some(Object::Nil), vm.list_tail(Object::Nil, 0));
+    assert_eq!(None, vm.list_tail(Object::Nil, 1));
+
+    let c = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(Some(vm.int(2)), vm.list_tail(c, 1));
+    assert_eq!(None, vm.list_tail(c, 2));
+}
+
+#[test]
+fn list_parts() {
+    let mut vm = VM::new();
+    let list = vm.read("(1 2 3)").unwrap();
+    let (items, tail) = vm.list_parts(list);
+    assert_eq!(vec![vm.int(1), vm.int(2), vm.int(3)], items);
+    assert_eq!(Object::Nil, tail);
+
+    let list = vm.read("(a b . rest)").unwrap();
+    let (items, tail) = vm.list_parts(list);
+    assert_eq!(2, items.len());
+    assert_eq!(Some("rest"), vm.symbol_name(tail));
+
+    assert_eq!((vec![], vm.int(5)), vm.list_parts(vm.int(5)));
+    assert_eq!((vec![], Object::Nil), vm.list_parts(Object::Nil));
+
+    let a = vm.cons(vm.int(1), Object::Nil);
+    let b = vm.cons(vm.int(2), a);
+    let cycle = vm.cons(vm.int(0), b);
+    vm.set_cdr(a, b).unwrap();
+    let (items, tail) = vm.list_parts(cycle);
+    assert!(items.len() <= 6);
+    assert!(vm.car(tail).is_some());
+    assert_eq!(None, vm.list_to_vec(cycle));
+}
+
+#[test]
+fn traversal() {
+    let mut vm = VM::new();
+    let list = vm.read("(define (f x) (g x 1))").unwrap();
+    assert_eq!(Some(3), vm.length(list));
+    assert_eq!(Some("define"), vm.symbol_name(vm.nth(list, 0).unwrap()));
+    let body = vm.nth(list, 2).unwrap();
+    assert_eq!(Some(vm.int(1)), vm.nth(body, 2));
+    assert_eq!(None, vm.nth(body, 3));
+    let names: Vec<_> = vm.iter_list(body).filter_map(|item| vm.symbol_name(item)).collect();
+    assert_eq!(vec!["g", "x"], names);
+
+    assert_eq!(Some(0), vm.length(Object::Nil));
+    assert_eq!(None, vm.nth(Object::Nil, 0));
+    assert_eq!(0, vm.iter_list(vm.int(1)).count());
+
+    let dotted = vm.read("(a b . c)").unwrap();
+    assert_eq!(None, vm.length(dotted));
+    assert_eq!(2, vm.iter_list(dotted).count());
+    assert!(vm.nth(dotted, 1).is_some());
+    assert_eq!(None, vm.nth(dotted, 2));
+    assert_eq!(None, vm.nth(dotted, 3));
+
+    let a = vm.cons(vm.int(1), Object::Nil);
+    let cycle = vm.cons(vm.int(0), a);
+    vm.set_cdr(a, cycle).unwrap();
+    assert_eq!(None, vm.length(cycle));
+    assert!(vm.iter_list(cycle).count() <= 4);
+    assert_eq!(Some(vm.int(1)), vm.nth(cycle, 5));
+}
+
+#[test]
+fn shares_tail() {
+    let mut vm = VM::new();
+    let tail = vm.list(&[vm.int(3), vm.int(4)]);
+    let a = vm.cons(vm.int(1), tail);
+    let b = vm.cons(vm.int(2), tail);
+    let c = vm.list(&[vm.int(3), vm.int(4)]);
+    assert!(vm.shares_tail(a, b));
+    assert!(vm.shares_tail(a, tail));
+    assert!(vm.shares_tail(a, a));
+    assert!(!vm.shares_tail(a, c));
+    assert!(!vm.shares_tail(Object::Nil, Object::Nil));
+    assert!(!vm.shares_tail(vm.int(1), a));
+
+    let appended = vm.append(c, tail).unwrap();
+    assert!(vm.shares_tail(appended, b));
+    assert!(!vm.shares_tail(appended, c));
+
+    // Cycles are walked once around
+    let x = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+    let last = vm.drop(x, 2).unwrap();
+    vm.set_cdr(last, x).unwrap();
+    let y = vm.cons(vm.int(0), last);
+    let z = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+    assert!(vm.shares_tail(x, y));
+    assert!(!vm.shares_tail(x, z));
+}
+
+#[test]
+fn list_gc() {
+    let mut vm = VmBuilder::new().gc_threshold(10).auto_gc(true).build();
+    let s = vm.string("s".to_string());
+    let list = vm.list(&[s, s, s]).root(&mut vm);
+    for _ in 0..10 {
+        vm.cons(Object::Nil, Object::Nil);
+    }
+    let list = vm.list(&[list.unroot(); 20]);
+    assert_eq!(24, vm.heapsize());
+    assert_eq!(Some(s), vm.car(vm.car(list).unwrap()));
+
+    let mut vm = VmBuilder::new().heap_limit(0).build();
+    assert_eq!(Object::Undef, vm.list(&[Object::Nil]));
+    assert_eq!(Object::Nil, vm.list(&[]));
+}
+
+#[test]
+fn memv_assv() {
+    let mut vm = VM::new();
+    let half = vm.rational(1, 2).unwrap();
+    let key = vm.list(&[vm.int(1)]);
+    let list = vm.list(&[vm.int(1), vm.char('a'), half, key]);
+
+    let found = vm.memv(vm.int(1), list).unwrap();
+    assert_eq!(list, found);
+    let found = vm.memv(vm.char('a'), list).unwrap();
+    assert_eq!("(#\\a 1/2 (1))", format!("{}", vm.wrap(found)));
+    let other_half = vm.rational(2, 4).unwrap();
+    assert!(vm.memv(other_half, list).is_some());
+    let found = vm.memv(key, list).unwrap();
+    assert_eq!("((1))", format!("{}", vm.wrap(found)));
+    let fresh = vm.list(&[vm.int(1)]);
+    assert!(vm.equal(key, fresh));
+    assert_eq!(None, vm.memv(fresh, list));
+    assert_eq!(None, vm.memv(vm.int(2), list));
+
+    // Floats are compared by their bits
+    assert!(!vm.eqv(vm.float(0.0), vm.float(-0.0)));
+    assert!(vm.eqv(vm.float(f64::NAN), vm.float(f64::NAN)));
+    assert!(vm.eqv(vm.float(1.5), vm.float(1.5)));
+    assert!(!vm.eqv(vm.float(1.0), vm.int(1)));
+    let floats = vm.list(&[vm.float(0.0), vm.float(f64::NAN)]);
+    assert_eq!(None, vm.memv(vm.float(-0.0), floats));
+    assert!(vm.memv(vm.float(f64::NAN), floats).is_some());
+
+    let a = vm.cons(vm.int(1), vm.char('x'));
+    let b = vm.cons(half, vm.char('y'));
+    let c = vm.cons(key, vm.char('z'));
+    let alist = vm.list(&[vm.int(5), a, b, c]);
+    assert_eq!(Some(a), vm.assv(vm.int(1), alist));
+    assert_eq!(Some(b), vm.assv(other_half, alist));
+    assert_eq!(Some(c), vm.assv(key, alist));
+    assert_eq!(None, vm.assv(fresh, alist));
+    assert_eq!(None, vm.assv(vm.int(5), alist));
+}
+
+#[test]
+fn alist_set() {
+    let mut vm = VM::new();
+    let alist = vm.read("((a . 1) (\"b\" . 2) (c . 3))").unwrap();
+    let key = vm.read("\"b\"").unwrap();
+
+    let updated = vm.alist_set(alist, key, vm.int(20));
+    assert_eq!("((a . 1) (\"b\" . 20) (c . 3))", format!("{}", vm.wrap(updated)));
+    assert_eq!("((a . 1) (\"b\" . 2) (c . 3))", format!("{}", vm.wrap(alist)));
+
+    // Pairs are shared, and so is the spine after the binding
+    assert_eq!(vm.car(alist), vm.car(updated));
+    assert_ne!(vm.cdr(alist), vm.cdr(updated));
+    assert_eq!(vm.list_tail(alist, 2), vm.list_tail(updated, 2));
+
+    let d = vm.symbol("d");
+    let added = vm.alist_set(alist, d, vm.int(4));
+    assert_eq!("((d . 4) (a . 1) (\"b\" . 2) (c . 3))", format!("{}", vm.wrap(added)));
+    assert_eq!(Some(alist), vm.cdr(added));
+
+    let empty = vm.alist_set(Object::Nil, d, vm.int(4));
+    assert_eq!("((d . 4))", format!("{}", vm.wrap(empty)));
+}
+
+#[test]
+fn set_operations() {
+    let mut vm = VM::new();
+    let a = vm.read("(1 \"x\" (2 3) 1 4)").unwrap();
+    let b = vm.read("(4 (2 3) 5 5 \"y\")").unwrap();
+    let show = |vm: &VM, obj: Option<Object>| format!("{}", vm.wrap(obj.unwrap()));
+
+    let r = vm.list_union(a, b);
+    assert_eq!("(1 \"x\" (2 3) 4 5 \"y\")", show(&vm, r));
+    let r = vm.list_intersection(a, b);
+    assert_eq!("((2 3) 4)", show(&vm, r));
+    let r = vm.list_difference(a, b);
+    assert_eq!("(1 \"x\")", show(&vm, r));
+    let r = vm.list_difference(b, a);
+    assert_eq!("(5 \"y\")", show(&vm, r));
+
+    let r = vm.list_union(Object::Nil, b);
+    assert_eq!("(4 (2 3) 5 \"y\")", show(&vm, r));
+    assert_eq!(Some(Object::Nil), vm.list_intersection(a, Object::Nil));
+    let r = vm.list_difference(a, Object::Nil);
+    assert_eq!("(1 \"x\" (2 3) 4)", show(&vm, r));
+
+    let improper = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(None, vm.list_union(a, improper));
+    assert_eq!(None, vm.list_intersection(improper, a));
+    assert_eq!(None, vm.list_difference(a, vm.int(1)));
+}
+
+#[test]
+fn for_each_list() {
+    let mut vm = VM::new();
+    let l = vm.read("(1 2 3)").unwrap();
+    let mut seen = Vec::new();
+    let heapsize = vm.heapsize();
+    assert_eq!(Some(()), vm.for_each_list(l, |vm, item| seen.push(format!("{}", vm.wrap(item)))));
+    assert_eq!(vec!["1", "2", "3"], seen);
+    assert_eq!(heapsize, vm.heapsize());
+
+    // The function may use the VM
+    let total = vm.cons(vm.int(0), Object::Nil);
+    vm.for_each_list(l, |vm, item| {
+        let sum = vm.add(vm.car(total).unwrap(), item).unwrap();
+        vm.set_car(total, sum).unwrap();
+    }).unwrap();
+    assert_eq!(Some(vm.int(6)), vm.car(total));
+
+    let mut count = 0;
+    assert_eq!(Some(()), vm.for_each_list(Object::Nil, |_, _| count += 1));
+    let improper = vm.read("(1 2 . 3)").unwrap();
+    assert_eq!(None, vm.for_each_list(improper, |_, _| count += 1));
+    let cycle = vm.read("(1 2)").unwrap();
+    let last = vm.cdr(cycle).unwrap();
+    vm.set_cdr(last, cycle).unwrap();
+    assert_eq!(None, vm.for_each_list(cycle, |_, _| count += 1));
+    assert_eq!(0, count);
+
+    // Cutting the list short or making it cyclic does not affect the
+    // walk, and the detached elements stay alive
+    let l = vm.read("(\"a\" \"b\" \"c\")").unwrap();
+    let _rooted = l.root(&mut vm);
+    let mut seen = Vec::new();
+    vm.for_each_list(l, |vm, item| {
+        vm.set_cdr(l, Object::Nil).unwrap();
+        vm.gc();
+        seen.push(format!("{}", vm.wrap(item)));
+    }).unwrap();
+    assert_eq!(vec!["\"a\"", "\"b\"", "\"c\""], seen);
+    let l = vm.read("(1 2 3)").unwrap();
+    let mut count = 0;
+    vm.for_each_list(l, |vm, _| {
+        vm.set_cdr(l, l).unwrap();
+        count += 1;
+    }).unwrap();
+    assert_eq!(3, count);
+}
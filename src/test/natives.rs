@@ -0,0 +1,32 @@
+//! Native procedures shared by the tests.
+
+use crate::*;
+
+pub fn add(vm: &mut VM, args: &[Object]) -> Object {
+    vm.add(args[0], args[1]).unwrap_or(Object::Undef)
+}
+
+pub fn sub(vm: &mut VM, args: &[Object]) -> Object {
+    vm.sub(args[0], args[1]).unwrap_or(Object::Undef)
+}
+
+pub fn sum(vm: &mut VM, args: &[Object]) -> Object {
+    let mut total = vm.int(0);
+    for arg in args {
+        total = vm.add(total, *arg).unwrap_or(Object::Undef);
+    }
+    total
+}
+
+pub fn is_zero(vm: &mut VM, args: &[Object]) -> Object {
+    vm.boolean(args[0] == vm.int(0))
+}
+
+/// Allocate a fresh string, so that every call allocates.
+pub fn make(vm: &mut VM, _: &[Object]) -> Object {
+    vm.string("x".to_string())
+}
+
+pub fn pair(vm: &mut VM, args: &[Object]) -> Object {
+    vm.cons(args[0], args[1])
+}
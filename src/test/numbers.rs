@@ -0,0 +1,293 @@
+use crate::*;
+
+#[test]
+fn expt() {
+    let mut vm = VM::new();
+    assert_eq!(Some(vm.int(1024)), vm.expt(vm.int(2), vm.int(10)));
+    assert_eq!(Some(vm.int(1)), vm.expt(vm.int(7), vm.int(0)));
+    assert_eq!(Some(vm.int(-8)), vm.expt(vm.int(-2), vm.int(3)));
+    assert_eq!(Some(vm.int(1)), vm.expt(vm.int(-1), vm.int(isize::MAX - 1)));
+    #[cfg(not(feature = "bignum"))]
+    assert_eq!(None, vm.expt(vm.int(10), vm.int(100)));
+
+    assert_eq!(Some(vm.float(0.25)), vm.expt(vm.int(2), vm.int(-2)));
+    assert_eq!(Some(vm.float(2.0f64.sqrt())), vm.expt(vm.float(2.0), vm.float(0.5)));
+    assert_eq!(Some(vm.float(8.0)), vm.expt(vm.float(2.0), vm.int(3)));
+    assert_eq!(Some(vm.float(3.0)), vm.expt(vm.int(9), vm.float(0.5)));
+
+    assert_eq!(None, vm.expt(Object::Nil, vm.int(2)));
+    assert_eq!(None, vm.expt(vm.int(2), Object::True));
+}
+
+#[test]
+fn rounding() {
+    let vm = VM::new();
+    assert_eq!(Some(vm.float(-5.0)), vm.floor(vm.float(-4.3)));
+    assert_eq!(Some(vm.float(-4.0)), vm.ceiling(vm.float(-4.3)));
+    assert_eq!(Some(vm.float(-4.0)), vm.truncate(vm.float(-4.3)));
+    assert_eq!(Some(vm.float(-4.0)), vm.round(vm.float(-4.3)));
+
+    assert_eq!(Some(vm.float(3.0)), vm.floor(vm.float(3.5)));
+    assert_eq!(Some(vm.float(4.0)), vm.ceiling(vm.float(3.5)));
+    assert_eq!(Some(vm.float(3.0)), vm.truncate(vm.float(3.5)));
+    assert_eq!(Some(vm.float(4.0)), vm.round(vm.float(3.5)));
+    assert_eq!(Some(vm.float(2.0)), vm.round(vm.float(2.5)));
+    assert_eq!(Some(vm.float(-2.0)), vm.round(vm.float(-2.5)));
+
+    assert_eq!(Some(vm.int(7)), vm.floor(vm.int(7)));
+    assert_eq!(Some(vm.int(7)), vm.ceiling(vm.int(7)));
+    assert_eq!(Some(vm.int(7)), vm.round(vm.int(7)));
+    assert_eq!(Some(vm.int(7)), vm.truncate(vm.int(7)));
+
+    assert_eq!(None, vm.floor(Object::Nil));
+    assert_eq!(None, vm.round(Object::True));
+}
+
+#[test]
+fn exactness() {
+    let mut vm = VM::new();
+    assert_eq!(Some(vm.float(3.0)), vm.exact_to_inexact(vm.int(3)));
+    assert_eq!(Some(vm.float(-2.5)), vm.exact_to_inexact(vm.float(-2.5)));
+    assert_eq!(None, vm.exact_to_inexact(Object::Nil));
+
+    assert_eq!(Some(vm.int(3)), vm.inexact_to_exact(vm.float(3.0)));
+    assert_eq!(Some(vm.int(-4)), vm.inexact_to_exact(vm.float(-4.0)));
+    assert_eq!(Some(vm.int(5)), vm.inexact_to_exact(vm.int(5)));
+    assert_eq!(None, vm.inexact_to_exact(vm.float(2.5)));
+    assert_eq!(None, vm.inexact_to_exact(vm.float(1e300)));
+    assert_eq!(None, vm.inexact_to_exact(vm.float(f64::INFINITY)));
+    assert_eq!(None, vm.inexact_to_exact(vm.float(f64::NAN)));
+    assert_eq!(None, vm.inexact_to_exact(Object::False));
+
+    let half = vm.rational(1, 2).unwrap();
+    assert_eq!(Some(half), vm.inexact_to_exact(half));
+}
+
+#[test]
+fn rationals() {
+    let mut vm = VM::new();
+    let half = vm.rational(1, 2).unwrap();
+    assert_eq!("1/2", format!("{}", vm.wrap(half)));
+    let r = vm.rational(6, -8).unwrap();
+    assert_eq!("-3/4", format!("{}", vm.wrap(r)));
+    assert_eq!(Some(vm.int(3)), vm.rational(6, 2));
+    assert_eq!(Some(vm.int(0)), vm.rational(0, 5));
+    assert_eq!(None, vm.rational(1, 0));
+
+    let a = vm.rational(1, 3).unwrap();
+    let b = vm.rational(1, 6).unwrap();
+    let r = vm.add(a, b).unwrap();
+    assert_eq!("1/2", format!("{}", vm.wrap(r)));
+    let r = vm.sub(a, b).unwrap();
+    assert_eq!("1/6", format!("{}", vm.wrap(r)));
+    let r = vm.mul(a, b).unwrap();
+    assert_eq!("1/18", format!("{}", vm.wrap(r)));
+    assert_eq!(Some(vm.int(2)), vm.div(a, b));
+    assert_eq!(Some(vm.int(1)), vm.add(half, half));
+
+    let r = vm.add(half, vm.int(1)).unwrap();
+    assert_eq!("3/2", format!("{}", vm.wrap(r)));
+    let r = vm.div(vm.int(3), vm.int(4)).unwrap();
+    assert_eq!("3/4", format!("{}", vm.wrap(r)));
+    assert_eq!(Some(vm.float(1.5)), vm.add(half, vm.float(1.0)));
+    assert_eq!(None, vm.div(half, vm.int(0)));
+
+    assert_eq!(Some(vm.float(0.5)), vm.exact_to_inexact(half));
+    let r = vm.rational(-7, 2).unwrap();
+    assert_eq!(Some(vm.int(-4)), vm.floor(r));
+    assert_eq!(Some(vm.int(-3)), vm.ceiling(r));
+    assert_eq!(Some(vm.int(-3)), vm.truncate(r));
+    assert_eq!(Some(vm.int(-4)), vm.round(r));
+    let r = vm.rational(5, 2).unwrap();
+    assert_eq!(Some(vm.int(2)), vm.round(r));
+    let r = vm.rational(7, 3).unwrap();
+    assert_eq!(Some(vm.int(2)), vm.round(r));
+}
+
+#[test]
+fn arithmetic() {
+    let mut vm = VM::new();
+    assert_eq!(Some(vm.int(5)), vm.add(vm.int(2), vm.int(3)));
+    assert_eq!(Some(vm.int(-1)), vm.sub(vm.int(2), vm.int(3)));
+    assert_eq!(Some(vm.int(6)), vm.mul(vm.int(2), vm.int(3)));
+    assert_eq!(Some(vm.int(2)), vm.div(vm.int(6), vm.int(3)));
+    assert_eq!(Some(vm.float(5.5)), vm.add(vm.int(2), vm.float(3.5)));
+    assert_eq!(Some(vm.float(f64::INFINITY)), vm.div(vm.float(1.0), vm.int(0)));
+
+    #[cfg(not(feature = "bignum"))]
+    assert_eq!(None, vm.add(vm.int(isize::MAX), vm.int(1)));
+    #[cfg(not(feature = "bignum"))]
+    assert_eq!(None, vm.mul(vm.int(isize::MAX), vm.int(2)));
+    assert_eq!(None, vm.add(vm.int(1), Object::Nil));
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn bignums() {
+    let mut vm = VM::new();
+    let big = vm.add(vm.int(isize::MAX), vm.int(1)).unwrap();
+    assert_eq!(format!("{}", (isize::MAX as i128) + 1), format!("{}", vm.wrap(big)));
+    assert_eq!(Some(vm.int(isize::MAX)), vm.sub(big, vm.int(1)));
+
+    let r = vm.expt(vm.int(10), vm.int(30)).unwrap();
+    assert_eq!("1000000000000000000000000000000", format!("{}", vm.wrap(r)));
+    let r = vm.mul(r, r).unwrap();
+    assert_eq!(format!("1{}", "0".repeat(60)), format!("{}", vm.wrap(r)));
+
+    let mut fact = vm.int(1);
+    for i in 1..=25 {
+        fact = vm.mul(fact, vm.int(i)).unwrap();
+    }
+    assert_eq!("15511210043330985984000000", format!("{}", vm.wrap(fact)));
+    assert_eq!(Some(vm.int(1)), vm.div(fact, fact));
+    assert_eq!(Some(vm.int(1 << 61)), vm.div(big, vm.int(4)));
+
+    let r = vm.add(big, vm.float(0.0)).unwrap();
+    assert_eq!(Some((isize::MAX as f64) + 1.0), r.as_float());
+    let half = vm.rational(1, 2).unwrap();
+    assert_eq!(Some(vm.int(1 << 62)), vm.mul(big, half));
+    let min = vm.div(vm.int(isize::MIN), vm.int(-1)).unwrap();
+    assert_eq!(Some(true), vm.num_equal(min, big));
+    let twice = vm.add(big, big).unwrap();
+    let r = vm.div(big, twice).unwrap();
+    assert!(vm.equal(r, half));
+    // Rationals with bignum parts are not supported
+    assert_eq!(None, vm.add(big, half));
+    assert_eq!(None, vm.div(big, vm.int(3)));
+    assert_eq!(None, vm.div(big, vm.int(0)));
+    let s = vm.int_to_string_radix(big, 16).unwrap();
+    assert_eq!(Some("8000000000000000"), vm.as_str(s));
+
+    let r = vm.expt(vm.int(10), vm.int(30)).unwrap();
+    let r = vm.add(r, vm.int(7)).unwrap();
+    let (root, rem) = vm.exact_integer_sqrt(r).unwrap();
+    assert_eq!("1000000000000000", format!("{}", vm.wrap(root)));
+    assert_eq!(Some(vm.int(7)), Some(rem));
+    let (root, rem) = vm.exact_integer_sqrt(big).unwrap();
+    assert_eq!(Some(vm.int(3037000499)), Some(root));
+    let square = vm.mul(root, root).unwrap();
+    let sum = vm.add(square, rem).unwrap();
+    assert_eq!(Some(true), vm.num_equal(big, sum));
+    let neg = vm.sub(vm.int(isize::MIN), vm.int(1)).unwrap();
+    assert_eq!(None, vm.exact_integer_sqrt(neg));
+
+    assert_eq!(Some(big), vm.inexact_to_exact(big));
+}
+
+#[test]
+fn num_equal() {
+    let mut vm = VM::new();
+    let half = vm.rational(1, 2).unwrap();
+    let other = vm.rational(2, 4).unwrap();
+    assert_eq!(Some(true), vm.num_equal(vm.int(1), vm.float(1.0)));
+    assert_eq!(Some(true), vm.num_equal(half, vm.float(0.5)));
+    assert_eq!(Some(true), vm.num_equal(half, other));
+    assert_eq!(Some(false), vm.num_equal(vm.int(1), vm.int(2)));
+    assert_eq!(Some(true), vm.num_equal(vm.float(0.0), vm.float(-0.0)));
+    assert_eq!(Some(false), vm.num_equal(vm.float(f64::NAN), vm.float(f64::NAN)));
+    assert_eq!(None, vm.num_equal(vm.int(1), Object::True));
+    let s = vm.string("1".to_string());
+    assert_eq!(None, vm.num_equal(s, vm.int(1)));
+    assert!(!vm.equal(vm.int(1), vm.float(1.0)));
+}
+
+fn radix(vm: &mut VM, v: isize, radix: u32) -> Option<String> {
+    let s = vm.int_to_string_radix(vm.int(v), radix)?;
+    vm.as_str(s).map(String::from)
+}
+
+#[test]
+fn int_to_string_radix() {
+    let mut vm = VM::new();
+    assert_eq!(Some("ff".to_string()), radix(&mut vm, 255, 16));
+    assert_eq!(Some("-101".to_string()), radix(&mut vm, -5, 2));
+    assert_eq!(Some("17".to_string()), radix(&mut vm, 15, 8));
+    assert_eq!(Some("0".to_string()), radix(&mut vm, 0, 10));
+    assert_eq!(Some("z".to_string()), radix(&mut vm, 35, 36));
+    assert_eq!(Some(format!("-8{}", "0".repeat(15))), radix(&mut vm, isize::MIN, 16));
+    assert_eq!(None, radix(&mut vm, 1, 1));
+    assert_eq!(None, radix(&mut vm, 1, 37));
+    assert_eq!(None, vm.int_to_string_radix(vm.float(1.0), 10));
+    let half = vm.rational(1, 2).unwrap();
+    assert_eq!(None, vm.int_to_string_radix(half, 10));
+}
+
+#[test]
+fn string_to_number() {
+    let mut vm = VM::new();
+    assert_eq!(Some(vm.int(42)), vm.string_to_number("42"));
+    assert_eq!(Some(vm.int(-7)), vm.string_to_number("-7"));
+    assert_eq!(Some(vm.float(2.5)), vm.string_to_number("2.5"));
+    let r = vm.string_to_number("3/4").unwrap();
+    assert_eq!("3/4", format!("{}", vm.wrap(r)));
+    assert_eq!(Some(vm.int(2)), vm.string_to_number("6/3"));
+
+    assert_eq!(Some(vm.int(255)), vm.string_to_number("#xff"));
+    assert_eq!(Some(vm.int(255)), vm.string_to_number("#XFF"));
+    assert_eq!(Some(vm.int(-8)), vm.string_to_number("#o-10"));
+    assert_eq!(Some(vm.int(5)), vm.string_to_number("#b101"));
+    assert_eq!(Some(vm.int(12)), vm.string_to_number("#d12"));
+    let r = vm.string_to_number("#x1/10").unwrap();
+    assert_eq!("1/16", format!("{}", vm.wrap(r)));
+
+    for bad in ["", "abc", "nil", "1/0", "1/-2", "#xg", "#b102", "#x", "#z1", "#x1.5", "1 2", "#xff/"] {
+        assert_eq!(None, vm.string_to_number(bad), "{}", bad);
+    }
+
+    let expr = vm.read("(#xff #b-11 #o7/10)").unwrap();
+    assert_eq!("(255 -3 7/8)", format!("{}", vm.wrap(expr)));
+    assert!(vm.read("#q1").is_err());
+}
+
+#[test]
+fn exact_integer_sqrt() {
+    let mut vm = VM::new();
+    let sqrt = |vm: &mut VM, v: isize| vm.exact_integer_sqrt(vm.int(v)).map(|(r, m)| (r.as_int().unwrap(), m.as_int().unwrap()));
+    assert_eq!(Some((0, 0)), sqrt(&mut vm, 0));
+    assert_eq!(Some((1, 0)), sqrt(&mut vm, 1));
+    assert_eq!(Some((2, 0)), sqrt(&mut vm, 4));
+    assert_eq!(Some((2, 1)), sqrt(&mut vm, 5));
+    assert_eq!(Some((3, 6)), sqrt(&mut vm, 15));
+    assert_eq!(Some((3037000499, 5928526806)), sqrt(&mut vm, isize::MAX));
+    assert_eq!(Some((3037000498, 6074000996)), sqrt(&mut vm, 3037000499 * 3037000499 - 1));
+    assert_eq!(None, sqrt(&mut vm, -4));
+    assert_eq!(None, vm.exact_integer_sqrt(vm.float(4.0)));
+    let r = vm.rational(1, 4).unwrap();
+    assert_eq!(None, vm.exact_integer_sqrt(r));
+}
+
+#[test]
+fn bitwise() {
+    let vm = VM::new();
+    let i = |v: isize| vm.int(v);
+    assert_eq!(Some(i(0b1000)), vm.bit_and(i(0b1100), i(0b1010)));
+    assert_eq!(Some(i(0b1110)), vm.bit_or(i(0b1100), i(0b1010)));
+    assert_eq!(Some(i(0b0110)), vm.bit_xor(i(0b1100), i(0b1010)));
+    assert_eq!(Some(i(4)), vm.bit_and(i(-4), i(7)));
+    assert_eq!(Some(i(-1)), vm.bit_or(i(-4), i(3)));
+    assert_eq!(Some(i(-7)), vm.bit_xor(i(-4), i(5)));
+    assert_eq!(Some(i(-1)), vm.bit_not(i(0)));
+    assert_eq!(Some(i(5)), vm.bit_not(i(-6)));
+    assert_eq!(Some(i(isize::MIN)), vm.bit_not(i(isize::MAX)));
+    assert_eq!(None, vm.bit_and(i(1), vm.float(1.0)));
+    assert_eq!(None, vm.bit_not(Object::Nil));
+
+    assert_eq!(Some(i(40)), vm.shift_left(i(5), i(3)));
+    assert_eq!(Some(i(-40)), vm.shift_left(i(-5), i(3)));
+    assert_eq!(Some(i(5)), vm.shift_left(i(5), i(0)));
+    assert_eq!(Some(i(isize::MIN)), vm.shift_left(i(-1), i(63)));
+    assert_eq!(Some(i(0)), vm.shift_left(i(0), i(1000)));
+    assert_eq!(None, vm.shift_left(i(1), i(63)));
+    assert_eq!(None, vm.shift_left(i(1), i(64)));
+    assert_eq!(None, vm.shift_left(i(-2), i(63)));
+    assert_eq!(None, vm.shift_left(i(1), i(-1)));
+
+    assert_eq!(Some(i(5)), vm.shift_right(i(40), i(3)));
+    assert_eq!(Some(i(-3)), vm.shift_right(i(-5), i(1)));
+    assert_eq!(Some(i(0)), vm.shift_right(i(5), i(1000)));
+    assert_eq!(Some(i(-1)), vm.shift_right(i(-5), i(1000)));
+    assert_eq!(Some(i(0)), vm.shift_right(i(5), i(isize::MAX)));
+    assert_eq!(Some(i(0)), vm.shift_left(i(0), i(isize::MAX)));
+    assert_eq!(None, vm.shift_right(i(5), i(-1)));
+    assert_eq!(None, vm.shift_right(i(5), vm.float(1.0)));
+}
@@ -0,0 +1,46 @@
+use crate::*;
+use super::natives::{sum, pair};
+
+#[test]
+fn natives() {
+    let mut vm = VM::new();
+    let f = vm.native(sum);
+    assert_eq!("#<native>", format!("{}", vm.wrap(f)));
+    assert_eq!(Some(vm.int(6)), vm.apply(f, &[vm.int(1), vm.int(2), vm.int(3)]));
+    assert_eq!(Some(vm.int(0)), vm.apply(f, &[]));
+    assert_eq!(Some(Object::Undef), vm.apply(f, &[Object::Nil]));
+
+    let g = vm.native(pair);
+    let r = vm.apply(g, &[vm.int(1), vm.int(2)]).unwrap();
+    assert_eq!("(1 . 2)", format!("{}", vm.wrap(r)));
+
+    assert_eq!(None, vm.apply(vm.int(1), &[]));
+    assert_eq!(None, vm.apply(r, &[]));
+
+    let rooted = f.root(&mut vm);
+    vm.gc();
+    assert_eq!(1, vm.heapsize());
+    assert_eq!(Some(vm.int(3)), vm.apply(rooted, &[vm.int(3)]));
+}
+
+#[test]
+fn apply_list() {
+    let mut vm = VM::new();
+    let f = vm.native(sum);
+    let args = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+    assert_eq!(Some(vm.int(6)), vm.apply_list(f, args));
+    assert_eq!(Some(vm.int(0)), vm.apply_list(f, Object::Nil));
+
+    let improper = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(None, vm.apply_list(f, improper));
+    assert_eq!(None, vm.apply_list(f, vm.int(1)));
+    assert_eq!(None, vm.apply_list(vm.int(1), args));
+
+    let env = vm.make_env(None);
+    let expr = vm.read("(lambda (x y) y)").unwrap();
+    let closure = vm.eval(expr, env).unwrap();
+    let args = vm.list(&[vm.int(1), vm.int(2)]);
+    assert_eq!(Some(vm.int(2)), vm.apply_list(closure, args));
+    let args = vm.list(&[vm.int(1)]);
+    assert_eq!(None, vm.apply_list(closure, args));
+}
@@ -0,0 +1,80 @@
+use crate::*;
+use super::natives::sum;
+
+fn force(vm: &mut VM, args: &[Object]) -> Object {
+    vm.force(args[0]).unwrap_or(Object::Undef)
+}
+
+fn setup(vm: &mut VM) -> Object {
+    let env = vm.make_env(None);
+    let names = ["+", "force"];
+    let procs = [vm.native(sum), vm.native(force)];
+    for (name, proc) in names.iter().zip(procs) {
+        let sym = vm.symbol(name);
+        vm.env_define(env, sym, proc).unwrap();
+    }
+    env
+}
+
+#[test]
+fn delay_force() {
+    let mut vm = VM::new();
+    let env = setup(&mut vm);
+    let expr = vm.read("(begin (define n 0) (delay (begin (set! n (+ n 1)) n)))").unwrap();
+    let p = vm.eval(expr, env).unwrap();
+    assert!(vm.is_promise(p));
+    assert_eq!("#<promise>", format!("{}", vm.wrap(p)));
+
+    // The body runs only once
+    assert_eq!(Some(vm.int(1)), vm.force(p));
+    assert_eq!(Some(vm.int(1)), vm.force(p));
+    let n = vm.symbol("n");
+    assert_eq!(Some(vm.int(1)), vm.env_lookup(env, n));
+
+    let f = vm.native(sum);
+    let p = vm.delay(f).unwrap();
+    assert_eq!(Some(vm.int(0)), vm.force(p));
+    assert_eq!(None, vm.delay(vm.int(5)));
+    assert_eq!(Some(vm.int(5)), vm.force(vm.int(5)));
+    assert!(!vm.is_promise(vm.int(5)));
+
+    let expr = vm.read("(delay 1 2)").unwrap();
+    assert!(vm.eval(expr, env).is_err());
+}
+
+#[test]
+fn reentrant() {
+    let mut vm = VM::new();
+    let env = setup(&mut vm);
+    let expr = vm.read("
+        (begin
+          (define n 0)
+          (define first #t)
+          (define p (delay (begin
+                             (set! n (+ n 1))
+                             (if first (begin (set! first #f) (force p) 100) n))))
+          (force p))
+    ").unwrap();
+    assert_eq!(Ok(vm.int(2)), vm.eval(expr, env));
+}
+
+#[test]
+fn gc() {
+    let mut vm = VM::new();
+    let env = setup(&mut vm);
+    let expr = vm.read("(delay (quote (1 2 3)))").unwrap();
+    let p = vm.eval(expr, env).unwrap().root(&mut vm);
+    let _env = env.root(&mut vm);
+
+    // The promise keeps its procedure alive, and then its value
+    vm.gc();
+    let value = vm.force(p.unroot()).unwrap();
+    assert_eq!("(1 2 3)", format!("{}", vm.wrap(value)));
+    vm.gc();
+    assert_eq!("(1 2 3)", format!("{}", vm.wrap(value)));
+    assert_eq!(Some(value), vm.force(p.unroot()));
+
+    let (mut copy, roots) = vm.snapshot(&[p.unroot()]).unwrap();
+    let value = copy.force(roots[0].unroot()).unwrap();
+    assert_eq!("(1 2 3)", format!("{}", copy.wrap(value)));
+}
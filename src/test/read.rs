@@ -0,0 +1,344 @@
+use proptest::prelude::*;
+
+use crate::*;
+
+fn read(vm: &mut VM, src: &str) -> String {
+    let obj = vm.read(src).unwrap();
+    format!("{}", vm.wrap(obj))
+}
+
+#[test]
+fn atoms() {
+    let mut vm = VM::new();
+    assert_eq!(Ok(vm.int(-12)), vm.read("-12"));
+    assert_eq!(Ok(vm.float(2.5)), vm.read("  2.5 ; comment"));
+    assert_eq!(Ok(Object::True), vm.read("#t"));
+    assert_eq!(Ok(Object::False), vm.read("#false"));
+    assert_eq!(Ok(Object::Nil), vm.read("nil"));
+    let sym = vm.symbol("-");
+    assert_eq!(Ok(sym), vm.read("-"));
+    let sym = vm.symbol("...");
+    assert_eq!(Ok(sym), vm.read("..."));
+    assert_eq!("3/4", read(&mut vm, "6/8"));
+    assert_eq!(Ok(vm.float(f64::NEG_INFINITY)), vm.read("-inf.0"));
+    assert!(vm.read("+nan.0").unwrap().as_float().unwrap().is_nan());
+    assert_eq!("\"a\\nb\"", read(&mut vm, "\"a\\nb\""));
+}
+
+#[test]
+fn compound() {
+    let mut vm = VM::new();
+    assert_eq!("nil", read(&mut vm, "()"));
+    assert_eq!("(1 (2 3) . 4)", read(&mut vm, "(1 (2 3) . 4)"));
+    assert_eq!("(quote (a b))", read(&mut vm, "'(a b)"));
+    assert_eq!("#(1 \"x\" #())", read(&mut vm, "#(1 \"x\" #())"));
+    assert_eq!("(a b)", read(&mut vm, "(a ; first\n b)"));
+}
+
+#[test]
+fn gc_while_reading() {
+    let mut vm = VmBuilder::new().gc_threshold(0).auto_gc(true).build();
+    let src = "(\"a\" \"b\" \"c\" \"d\" \"e\" \"f\" \"g\" \"h\" \"i\" \"j\")";
+    assert_eq!(src, read(&mut vm, src));
+    assert_eq!("(\"a\" (\"b\") . \"c\")", read(&mut vm, "(\"a\" (\"b\") . \"c\")"));
+    assert_eq!("#(\"a\" #(\"b\") \"c\")", read(&mut vm, "#(\"a\" #(\"b\") \"c\")"));
+    assert_eq!("(quote \"a\")", read(&mut vm, "'\"a\""));
+}
+
+#[test]
+fn control_characters() {
+    let mut vm = VM::new();
+    for c in ('\0'..='\x1f').chain(['\x7f']) {
+        let obj = vm.string(format!("a{}", c));
+        let printed = format!("{}", vm.wrap(obj));
+        let read = vm.read(&printed);
+        assert_eq!(Some(vm.as_str(obj).unwrap()), read.ok().and_then(|s| vm.as_str(s)), "{}", printed);
+        let sym = vm.symbol(&format!("a b{}", c));
+        let printed = format!("{}", vm.wrap(sym));
+        assert_eq!(Ok(sym), vm.read(&printed), "{}", printed);
+    }
+}
+
+#[test]
+fn symbols() {
+    let mut vm = VM::new();
+    for (name, printed) in [
+        ("foo", "foo"),
+        ("+", "+"),
+        ("1+", "1+"),
+        ("a\\b", "a\\b"),
+        ("hello world", "|hello world|"),
+        ("(x)", "|(x)|"),
+        ("a|b", "|a\\|b|"),
+        ("", "||"),
+        ("12", "|12|"),
+        ("-inf.0", "|-inf.0|"),
+        (".", "|.|"),
+        ("nil", "|nil|"),
+        ("#t", "|#t|"),
+        ("tab\t", "|tab\\t|"),
+    ] {
+        let sym = vm.symbol(name);
+        assert_eq!(printed, format!("{}", vm.wrap(sym)));
+        assert_eq!(Ok(sym), vm.read(printed), "{}", printed);
+    }
+    let sym = vm.symbol("foo");
+    assert_eq!(Ok(sym), vm.read("|foo|"));
+    assert_eq!("(a b c)", read(&mut vm, "(a|b|c)"));
+    assert_eq!("unclosed symbol", vm.read("|abc").unwrap_err().message);
+}
+
+#[test]
+fn errors() {
+    let mut vm = VM::new();
+    let err = vm.read("(a\n  b))").unwrap_err();
+    assert_eq!((7, 2, 5), (err.offset, err.line, err.column));
+    assert_eq!(")", err.snippet);
+    assert_eq!("2:5: unexpected trailing input", format!("{}", err));
+
+    let err = vm.read("(1 #q)").unwrap_err();
+    assert_eq!((3, 1, 4), (err.offset, err.line, err.column));
+    assert_eq!("#q", err.snippet);
+    assert_eq!("unknown # syntax", err.message);
+
+    let err = vm.read("(λ \"\\z\")").unwrap_err();
+    assert_eq!((1, 4), (err.line, err.column));
+    assert_eq!("\"\\z\"", err.snippet);
+
+    assert_eq!("unclosed list", vm.read("(1 (2)").unwrap_err().message);
+    assert_eq!("unexpected '.'", vm.read(". 1").unwrap_err().message);
+    assert_eq!("unclosed list", vm.read("(1 . ").unwrap_err().message);
+    assert_eq!("unclosed list", vm.read("(1 . 2").unwrap_err().message);
+}
+
+#[test]
+fn number_errors() {
+    let mut vm = VM::new();
+    for input in ["1/0", "-3/0", "#x1/0", "(a 1/0)"] {
+        assert_eq!("zero denominator", vm.read(input).unwrap_err().message, "{}", input);
+    }
+    let err = vm.read("(a 1/0)").unwrap_err();
+    assert_eq!("1/0", err.snippet);
+    for input in ["99999999999999999999/2", "1/99999999999999999999", "#x1/ffffffffffffffffff"] {
+        assert_eq!("integer literal out of range", vm.read(input).unwrap_err().message, "{}", input);
+    }
+    assert_eq!("99999999999999999999a", read(&mut vm, "99999999999999999999a"));
+    assert_eq!(None, vm.string_to_number("1/0"));
+    assert_eq!(None, vm.string_to_number("99999999999999999999/2"));
+}
+
+#[cfg(not(feature = "bignum"))]
+#[test]
+fn integer_out_of_range() {
+    let mut vm = VM::new();
+    for input in ["99999999999999999999", "-9223372036854775809", "#xffffffffffffffffff"] {
+        assert_eq!("integer literal out of range", vm.read(input).unwrap_err().message, "{}", input);
+        assert_eq!(None, vm.string_to_number(input), "{}", input);
+    }
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn bignum_literals() {
+    let mut vm = VM::new();
+    for input in ["99999999999999999999", "-9223372036854775809", "+18446744073709551616"] {
+        let obj = vm.read(input).unwrap();
+        assert_eq!(input.trim_start_matches('+'), format!("{}", vm.wrap(obj)));
+        let n = vm.string_to_number(input).unwrap();
+        assert!(vm.equal(n, obj), "{}", input);
+    }
+    assert_eq!("4722366482869645213695", read(&mut vm, "#xffffffffffffffffff"));
+    assert_eq!(Ok(vm.int(isize::MIN)), vm.read("-9223372036854775808"));
+}
+
+#[test]
+fn dotted_errors() {
+    let mut vm = VM::new();
+    for input in ["(. 5)", "( . 5)", "(. 5 6)", "(.)"] {
+        let err = vm.read(input).unwrap_err();
+        assert_eq!("expected a datum before dot", err.message, "{}", input);
+        assert_eq!(".", err.snippet);
+    }
+    for input in ["(1 . 2 3)", "(1 . )", "(1 .)", "(1 . . 2)", "(1 . 2 . 3)", "(1 2 . (3) 4)"] {
+        let err = vm.read(input).unwrap_err();
+        assert_eq!("expected exactly one datum after dot", err.message, "{}", input);
+    }
+    let err = vm.read("(1 . 2 3)").unwrap_err();
+    assert_eq!((8, "3"), (err.column, err.snippet.as_str()));
+
+    let ok = vm.read("(1 . (2 . (3 . ())))").unwrap();
+    assert_eq!("(1 2 3)", format!("{}", vm.wrap(ok)));
+    assert_eq!("unexpected end of input", vm.read("  ").unwrap_err().message);
+}
+
+fn complete(vm: &mut VM, reader: &mut Reader) -> String {
+    match reader.next_datum(vm) {
+        Some(ReadOutcome::Complete(datum)) => format!("{}", vm.wrap(datum)),
+        outcome => panic!("expected a datum, got {:?}", outcome),
+    }
+}
+
+#[test]
+fn reader() {
+    let mut vm = VM::new();
+    let mut reader = Reader::new("1 (a ; comment\n b) #q 2");
+    assert_eq!(Some(ReadOutcome::Complete(vm.int(1))), reader.next_datum(&mut vm));
+    assert_eq!("(a b)", complete(&mut vm, &mut reader));
+    match reader.next_datum(&mut vm) {
+        Some(ReadOutcome::Error(err)) => assert_eq!((2, 5), (err.line, err.column)),
+        outcome => panic!("expected an error, got {:?}", outcome),
+    }
+    assert_eq!("2", complete(&mut vm, &mut reader));
+    assert_eq!(None, reader.next_datum(&mut vm));
+
+    let mut reader = Reader::new(String::from("(1 \"x"));
+    assert_eq!(Some(ReadOutcome::Incomplete), reader.next_datum(&mut vm));
+    assert_eq!(0, reader.offset());
+    reader.feed("\" 2)  ");
+    assert_eq!("(1 \"x\" 2)", complete(&mut vm, &mut reader));
+    assert_eq!(None, reader.next_datum(&mut vm));
+    assert_eq!(11, reader.offset());
+}
+
+#[test]
+fn max_depth() {
+    let mut vm = VM::new();
+    let nested = |n: usize| format!("{}{}", "(".repeat(n), ")".repeat(n));
+    assert!(vm.read(&nested(256)).is_ok());
+    let err = vm.read(&nested(257)).unwrap_err();
+    assert_eq!("maximum nesting depth exceeded", err.message);
+    assert_eq!((257, "("), (err.column, err.snippet.as_str()));
+
+    // Hostile input fails without overflowing the stack
+    assert!(vm.read(&nested(1_000_000)).is_err());
+    assert!(vm.read(&"'".repeat(1_000_000)).is_err());
+    assert!(vm.read(&"#(".repeat(1_000_000)).is_err());
+
+    let mut vm = VmBuilder::new().max_read_depth(2).build();
+    assert_eq!("(1 (2 3) #(4))", read(&mut vm, "(1 (2 3) #(4))"));
+    assert_eq!("(quote (1))", read(&mut vm, "'(1)"));
+    assert!(vm.read("(1 (2 (3)))").is_err());
+    assert!(vm.read("(#(#()))").is_err());
+    assert!(vm.read("''(1)").is_err());
+    let mut reader = Reader::new("((x)) (((y)))");
+    assert_eq!("((x))", complete(&mut vm, &mut reader));
+    assert!(matches!(reader.next_datum(&mut vm), Some(ReadOutcome::Error(_))));
+
+    let mut vm = VmBuilder::new().max_read_depth(0).build();
+    assert_eq!("x", read(&mut vm, "x"));
+    assert!(vm.read("()").is_err());
+}
+
+#[test]
+fn shebang() {
+    let mut vm = VM::new();
+    let mut reader = Reader::new("#!/usr/bin/env ginkgo -q\n(define x 1)\n#t");
+    assert_eq!("(define x 1)", complete(&mut vm, &mut reader));
+    assert_eq!("#t", complete(&mut vm, &mut reader));
+    assert_eq!(None, reader.next_datum(&mut vm));
+
+    assert_eq!("42", read(&mut vm, "#!/bin/ginkgo\n42"));
+    let mut reader = Reader::new("#!/bin/ginkgo");
+    assert_eq!(None, reader.next_datum(&mut vm));
+
+    // A shebang or comment split across feeds is still skipped
+    let mut reader = Reader::new(String::from("#!/usr/bin/en"));
+    assert_eq!(None, reader.next_datum(&mut vm));
+    reader.feed("v ginkgo");
+    assert_eq!(None, reader.next_datum(&mut vm));
+    reader.feed(" -q\n(a) ; com");
+    assert_eq!("(a)", complete(&mut vm, &mut reader));
+    assert_eq!(None, reader.next_datum(&mut vm));
+    reader.feed("ment\n42");
+    assert_eq!("42", complete(&mut vm, &mut reader));
+
+    // Only the first line of the source is skipped
+    assert!(vm.read(" #!/bin/ginkgo\n42").is_err());
+    assert!(vm.read("1\n#!/bin/ginkgo").is_err());
+    assert!(vm.read("#!a\n#!b\n1").is_err());
+}
+
+#[test]
+fn incomplete() {
+    let mut vm = VM::new();
+    for src in ["(", "(1 . ", "#(1 2", "\"abc\\", "'", "#"] {
+        let mut reader = Reader::new(src);
+        assert_eq!(Some(ReadOutcome::Incomplete), reader.next_datum(&mut vm), "{}", src);
+    }
+    for src in ["#q", "(1 #q", ")", "(1 . 2 3"] {
+        let mut reader = Reader::new(src);
+        assert!(matches!(reader.next_datum(&mut vm), Some(ReadOutcome::Error(_))), "{}", src);
+    }
+
+    let mut reader = Reader::new("(define (f x)\n");
+    assert_eq!(Some(ReadOutcome::Incomplete), reader.next_datum(&mut vm));
+    reader.feed("  x)\n");
+    assert_eq!("(define (f x) x)", complete(&mut vm, &mut reader));
+}
+
+/// Value tree that can be built in any VM.
+#[derive(Clone, Debug)]
+enum Value {
+    Int(isize),
+    Float(f64),
+    String(String),
+    Symbol(String),
+    List(Vec<Value>),
+    Vec(Vec<Value>),
+}
+
+impl Value {
+    fn build(&self, vm: &mut VM) -> Object {
+        match self {
+            Value::Int(v) => vm.int(*v),
+            Value::Float(v) => vm.float(*v),
+            Value::String(s) => vm.string(s.clone()),
+            Value::Symbol(s) => vm.symbol(s),
+            Value::List(items) => {
+                let items: Vec<_> = items.iter().map(|item| item.build(vm)).collect();
+                vm.list(&items)
+            }
+            Value::Vec(items) => {
+                let items: Vec<_> = items.iter().map(|item| item.build(vm)).collect();
+                vm.vec_from_iter(items)
+            }
+        }
+    }
+}
+
+/// Text for strings and symbols, weighted towards characters that need
+/// escaping when printed.
+fn text() -> impl Strategy<Value = String> {
+    let c = prop_oneof![
+        3 => proptest::char::range('\0', '\x1f'),
+        1 => Just('\x7f'),
+        3 => prop::sample::select(vec!['"', '|', '\\', '^', ' ']),
+        3 => any::<char>(),
+    ];
+    prop::collection::vec(c, 0..16).prop_map(|chars| chars.into_iter().collect())
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    use proptest::num::f64::{INFINITE, NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+    let leaf = prop_oneof![
+        any::<isize>().prop_map(Value::Int),
+        (POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE).prop_map(Value::Float),
+        text().prop_map(Value::String),
+        text().prop_map(Value::Symbol),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| prop_oneof![
+        prop::collection::vec(inner.clone(), 0..8).prop_map(Value::List),
+        prop::collection::vec(inner, 0..8).prop_map(Value::Vec),
+    ])
+}
+
+proptest! {
+    #[test]
+    fn round_trip(value in value()) {
+        let mut vm = VM::new();
+        let obj = value.build(&mut vm).root(&mut vm);
+        let printed = format!("{}", vm.wrap(obj.unroot()));
+        let read = vm.read(&printed).unwrap();
+        prop_assert!(vm.equal(obj.unroot(), read), "{}", printed);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::*;
+use super::natives::add;
+use std::thread;
+
+#[test]
+fn sendable() {
+    fn assert_send<T: Send>() {}
+    assert_send::<SendableVm>();
+
+    let mut vm = VmBuilder::new().gc_threshold(100).build();
+    let env = vm.make_env(None);
+    let plus = vm.symbol("+");
+    let native = vm.native(add);
+    vm.env_define(env, plus, native).unwrap();
+    let def = vm.read("(define (f x) (+ x 1/2))").unwrap();
+    vm.eval(def, env).unwrap();
+
+    let data = vm.read("(\"s\" #(1 2) . #\\c)").unwrap();
+    let v = vm.vec_from_iter(vec![data, Object::Nil]);
+    vm.vec_set(v, 1, v).unwrap();
+    vm.freeze(v);
+
+    let sendable = vm.into_sendable(&[env, v]).ok().unwrap();
+    let result = thread::spawn(move || {
+        let (mut vm, roots) = sendable.into_vm();
+        let call = vm.read("(f 1)").unwrap();
+        let r = vm.eval(call, roots[0].unroot()).unwrap();
+        assert_eq!("3/2", format!("{}", vm.wrap(r)));
+        let v = roots[1].unroot();
+        assert_eq!(Some(v), vm.vec_get(v, 1));
+        assert!(vm.is_frozen(v));
+        let roots: Vec<Object> = roots.iter().map(GObj::unroot).collect();
+        vm.into_sendable(&roots).ok().unwrap()
+    }).join().unwrap();
+
+    let (mut vm, roots) = result.into_vm();
+    vm.gc();
+    let data = vm.vec_get(roots[1].unroot(), 0).unwrap();
+    assert_eq!("(\"s\" #(1 2) . #\\c)", format!("{}", vm.wrap(data)));
+    let f = vm.symbol("f");
+    assert!(vm.env_lookup(roots[0].unroot(), f).is_some());
+}
+
+#[test]
+fn sendable_foreign() {
+    let mut vm = VM::new();
+    let obj = vm.foreign(Box::new(1));
+    let list = vm.list(&[obj]);
+    let _rooted = list.root(&mut vm);
+    let vm = match vm.into_sendable(&[list]) {
+        Ok(_) => panic!("foreign objects must not be sendable"),
+        Err(vm) => *vm,
+    };
+
+    // The VM is handed back intact
+    assert_eq!(Some(obj), vm.car(list));
+    assert_eq!(Some(&1), vm.foreign_ref::<i32>(obj));
+}
@@ -0,0 +1,150 @@
+use crate::*;
+use super::natives::{is_zero, make, pair};
+use std::mem::size_of;
+
+#[test]
+fn size_of_() {
+    let mut vm = VM::new();
+    let base = size_of::<HVal>();
+    assert_eq!(0, vm.size_of(vm.int(1)));
+    assert_eq!(0, vm.size_of(Object::Nil));
+
+    let pair = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(base, vm.size_of(pair));
+    let s = vm.string(String::with_capacity(100));
+    assert_eq!(base + 100, vm.size_of(s));
+    let v = vm.vec(10);
+    assert_eq!(base + 10 * size_of::<Object>(), vm.size_of(v));
+}
+
+#[test]
+fn deep_size() {
+    let mut vm = VM::new();
+    let base = size_of::<HVal>();
+    let s = vm.string("abcd".to_string());
+    let list = vm.list(&[s, s, vm.int(3)]);
+    assert_eq!(3 * base + vm.size_of(s), vm.deep_size(list));
+
+    let v = vm.vec(2);
+    vm.vec_set(v, 0, v).unwrap();
+    vm.vec_set(v, 1, list).unwrap();
+    assert_eq!(vm.size_of(v) + vm.deep_size(list), vm.deep_size(v));
+    assert_eq!(0, vm.deep_size(vm.float(1.0)));
+}
+
+#[test]
+fn heap_limit() {
+    let mut vm = VM::new();
+    let base = size_of::<HVal>();
+    vm.set_heap_limit(4 * base);
+
+    let a = vm.try_cons(Object::True, Object::Nil).unwrap();
+    let b = vm.try_cons(Object::False, a).unwrap();
+    let rooted = b.root(&mut vm);
+    assert_eq!(2 * base, vm.heap_bytes());
+    vm.try_string(String::new()).unwrap();
+    vm.try_cons(Object::Nil, Object::Nil).unwrap();
+    assert_eq!(4, vm.heapsize());
+
+    // Garbage is collected to make room.
+    let c = vm.try_cons(Object::Nil, Object::Nil).unwrap();
+    assert_eq!(3, vm.heapsize());
+    assert_eq!(3 * base, vm.heap_bytes());
+    let c = c.root(&mut vm);
+
+    assert_eq!(Err(GinkgoError::OutOfMemory), vm.try_vec(1));
+    assert_eq!(Err(GinkgoError::OutOfMemory), vm.try_string("x".repeat(base)));
+    assert_eq!(Object::Undef, vm.string("x".repeat(base)));
+    assert!(vm.try_cons(Object::Nil, Object::Nil).is_ok());
+
+    drop(c);
+    assert!(vm.try_vec(1).is_ok());
+    assert_eq!(Some(a), vm.cdr(rooted.unroot()));
+
+    vm.remove_heap_limit();
+    assert!(vm.try_string("x".repeat(10 * base)).is_ok());
+}
+
+#[test]
+fn heap_limit_builder() {
+    let mut vm = VmBuilder::new().heap_limit(0).build();
+    assert_eq!(Err(GinkgoError::OutOfMemory), vm.try_cons(Object::Nil, Object::Nil));
+    assert_eq!(Object::Undef, vm.cons(Object::Nil, Object::Nil));
+    assert_eq!(Ok(vm.int(1)), vm.read("1"));
+}
+
+#[test]
+fn heap_limit_growth() {
+    let mut vm = VM::new();
+    let v = vm.vec(0).root(&mut vm);
+    let limit = size_of::<HVal>() + 16 * size_of::<Object>();
+    vm.set_heap_limit(limit);
+    let mut pushed = 0;
+    while vm.vec_push(v.unroot(), vm.int(pushed)).is_ok() {
+        pushed += 1;
+    }
+    assert_eq!(Err(GinkgoError::OutOfMemory), vm.vec_push(v.unroot(), Object::Nil));
+    assert_eq!(pushed as usize, vm.vec_to_rust(v.unroot()).unwrap().len());
+    assert!(vm.heap_bytes() <= limit);
+    assert_eq!(vm.size_of(v.unroot()), vm.heap_bytes());
+
+    let mut vm = VM::new();
+    let s = vm.string("a".to_string()).root(&mut vm);
+    vm.set_heap_limit(vm.heap_bytes());
+    assert_eq!(Err(GinkgoError::OutOfMemory), vm.string_set(s.unroot(), 0, '€'));
+    assert_eq!(Some("a"), vm.as_str(s.unroot()));
+    assert_eq!(vm.size_of(s.unroot()), vm.heap_bytes());
+    assert_eq!(Ok(()), vm.string_set(s.unroot(), 0, 'b'));
+    assert_eq!(Some("b"), vm.as_str(s.unroot()));
+
+    let mut vm = VM::new();
+    let env = vm.make_env(None).root(&mut vm);
+    let x = vm.symbol("x");
+    vm.set_heap_limit(vm.heap_bytes());
+    assert_eq!(Err(GinkgoError::OutOfMemory), vm.env_define(env.unroot(), x, vm.int(1)));
+    assert_eq!(None, vm.env_lookup(env.unroot(), x));
+    assert_eq!(vm.size_of(env.unroot()), vm.heap_bytes());
+}
+
+fn sub1(vm: &mut VM, args: &[Object]) -> Object {
+    vm.int(args[0].as_int().unwrap() - 1)
+}
+
+#[test]
+fn heap_limit_eval() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None).root(&mut vm);
+    let natives: [(&str, NativeFn); 4] = [("mk", make), ("pair", pair), ("sub1", sub1), ("zero?", is_zero)];
+    for (name, f) in natives {
+        let sym = vm.symbol(name);
+        let native = vm.native(f);
+        vm.env_define(env.unroot(), sym, native).unwrap();
+    }
+    let eval = |vm: &mut VM, src: &str| {
+        let expr = vm.read(src).unwrap();
+        vm.eval(expr, env.unroot()).map(|obj| format!("{}", vm.wrap(obj)))
+    };
+    eval(&mut vm, "(define (build n acc) (if (zero? n) acc (build (sub1 n) (pair (mk) acc))))").unwrap();
+    eval(&mut vm, "(define (id x) x)").unwrap();
+
+    // Garbage is collected along the way, and live data survives
+    let base = size_of::<HVal>();
+    vm.set_heap_limit(vm.heap_bytes() + 200 * base);
+    let expected = format!("({})", vec!["\"x\""; 50].join(" "));
+    assert_eq!(Ok(expected), eval(&mut vm, "(build 50 nil)"));
+    assert_eq!(Err(GinkgoError::OutOfMemory), eval(&mut vm, "(build 1000 nil)"));
+
+    // Failing to allocate a frame or a closure is reported as such
+    for src in ["(id 1)", "(lambda (x) x)"] {
+        vm.remove_heap_limit();
+        let expr = vm.read(src).unwrap().root(&mut vm);
+        vm.gc();
+        vm.set_heap_limit(vm.heap_bytes());
+        assert_eq!(Err(GinkgoError::OutOfMemory), vm.eval(expr.unroot(), env.unroot()));
+    }
+
+    // So is failing to allocate while reading
+    vm.set_heap_limit(0);
+    let err = vm.read("(1 2)").unwrap_err();
+    assert_eq!("heap limit exceeded", err.message);
+}
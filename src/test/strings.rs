@@ -0,0 +1,168 @@
+use crate::*;
+
+#[test]
+fn string_chars() {
+    let mut vm = VM::new();
+    let s = vm.string("aλ b".to_string());
+    let chars = vm.string_chars(s).unwrap();
+    assert_eq!("(#\\a #\\λ #\\space #\\b)", format!("{}", vm.wrap(chars)));
+    let copy = vm.list_to_string(chars).unwrap();
+    assert!(vm.equal(s, copy));
+    assert_ne!(s, copy);
+
+    let empty = vm.string(String::new());
+    assert_eq!(Some(Object::Nil), vm.string_to_list(empty));
+    let copy = vm.list_to_string(Object::Nil).unwrap();
+    assert!(vm.equal(empty, copy));
+
+    assert_eq!(None, vm.string_chars(vm.int(1)));
+    let mixed = vm.list(&[vm.char('a'), vm.int(1)]);
+    assert_eq!(None, vm.list_to_string(mixed));
+    let improper = vm.cons(vm.char('a'), vm.char('b'));
+    assert_eq!(None, vm.list_to_string(improper));
+}
+
+#[test]
+fn string_join() {
+    let mut vm = VM::new();
+    let list = vm.read("(\"a\" \"\" \"bc\")").unwrap();
+    let sep = vm.string(", ".to_string());
+    let joined = vm.string_join(list, sep).unwrap();
+    assert_eq!("\"a, , bc\"", format!("{}", vm.wrap(joined)));
+
+    let one = vm.read("(\"only\")").unwrap();
+    let joined = vm.string_join(one, sep).unwrap();
+    assert_eq!("\"only\"", format!("{}", vm.wrap(joined)));
+    let joined = vm.string_join(Object::Nil, sep).unwrap();
+    assert_eq!("\"\"", format!("{}", vm.wrap(joined)));
+
+    let bad = vm.read("(\"a\" b)").unwrap();
+    assert_eq!(None, vm.string_join(bad, sep));
+    assert_eq!(None, vm.string_join(list, vm.char(',')));
+}
+
+#[test]
+fn string_split() {
+    let mut vm = VM::new();
+    let s = vm.string("a,,b,".to_string());
+    let sep = vm.string(",".to_string());
+    let parts = vm.string_split(s, sep).unwrap();
+    assert_eq!("(\"a\" \"\" \"b\" \"\")", format!("{}", vm.wrap(parts)));
+
+    let joined = vm.string_join(parts, sep).unwrap();
+    assert!(vm.equal(s, joined));
+
+    let s = vm.string("aλ".to_string());
+    let empty = vm.string(String::new());
+    let parts = vm.string_split(s, empty).unwrap();
+    assert_eq!("(\"a\" \"λ\")", format!("{}", vm.wrap(parts)));
+    assert_eq!(Some(Object::Nil), vm.string_split(empty, empty));
+    let parts = vm.string_split(empty, sep).unwrap();
+    assert_eq!("(\"\")", format!("{}", vm.wrap(parts)));
+
+    assert_eq!(None, vm.string_split(s, vm.char(',')));
+    assert_eq!(None, vm.string_split(Object::Nil, sep));
+}
+
+#[test]
+fn string_search() {
+    let mut vm = VM::new();
+    let s = vm.string("Hello world".to_string());
+    let hello = vm.string("Hello".to_string());
+    let world = vm.string("world".to_string());
+    let lower = vm.string("hello".to_string());
+    let empty = vm.string(String::new());
+
+    assert_eq!(Some(true), vm.string_contains(s, world));
+    assert_eq!(Some(true), vm.string_contains(s, empty));
+    assert_eq!(Some(false), vm.string_contains(s, lower));
+    assert_eq!(Some(true), vm.string_prefix(s, hello));
+    assert_eq!(Some(false), vm.string_prefix(s, world));
+    assert_eq!(Some(false), vm.string_prefix(s, lower));
+    assert_eq!(Some(true), vm.string_suffix(s, world));
+    assert_eq!(Some(false), vm.string_suffix(s, hello));
+    assert_eq!(Some(false), vm.string_suffix(empty, s));
+
+    assert_eq!(None, vm.string_contains(s, vm.char('H')));
+    assert_eq!(None, vm.string_prefix(Object::Nil, s));
+    assert_eq!(None, vm.string_suffix(vm.int(1), s));
+}
+
+#[test]
+fn string_replace() {
+    let mut vm = VM::new();
+    let s = vm.string("aaa-b-aaa".to_string());
+    let aa = vm.string("aa".to_string());
+    let x = vm.string("x".to_string());
+    let empty = vm.string(String::new());
+
+    let r = vm.string_replace(s, aa, x).unwrap();
+    assert_eq!("\"xa-b-xa\"", format!("{}", vm.wrap(r)));
+    let r = vm.string_replace(s, aa, empty).unwrap();
+    assert_eq!("\"a-b-a\"", format!("{}", vm.wrap(r)));
+    let r = vm.string_replace(s, x, aa).unwrap();
+    assert!(vm.equal(s, r));
+    assert_ne!(s, r);
+
+    assert_eq!(None, vm.string_replace(s, empty, x));
+    assert_eq!(None, vm.string_replace(s, aa, vm.char('x')));
+    assert_eq!(None, vm.string_replace(Object::Nil, aa, x));
+}
+
+#[test]
+fn string_trim() {
+    let mut vm = VM::new();
+    let s = vm.string(" \t a b \n".to_string());
+    let r = vm.string_trim(s).unwrap();
+    assert_eq!("\"a b\"", format!("{}", vm.wrap(r)));
+    let r = vm.string_trim_start(s).unwrap();
+    assert_eq!("\"a b \\n\"", format!("{}", vm.wrap(r)));
+    let r = vm.string_trim_end(s).unwrap();
+    assert_eq!("\" \\t a b\"", format!("{}", vm.wrap(r)));
+
+    let blank = vm.string("   ".to_string());
+    let r = vm.string_trim(blank).unwrap();
+    assert_eq!("\"\"", format!("{}", vm.wrap(r)));
+    assert_eq!(" \t a b \n", vm.as_str(s).unwrap());
+
+    assert_eq!(None, vm.string_trim(vm.char(' ')));
+    assert_eq!(None, vm.string_trim_start(Object::Nil));
+    assert_eq!(None, vm.string_trim_end(vm.int(0)));
+}
+
+#[test]
+fn make_string() {
+    let mut vm = VM::new();
+    let empty = vm.empty_string();
+    assert_eq!(Some(""), vm.as_str(empty));
+    assert_ne!(empty, vm.empty_string());
+
+    let s = vm.make_string(3, 'λ');
+    assert_eq!(Some("λλλ"), vm.as_str(s));
+    let s = vm.make_string(0, 'x');
+    assert!(vm.equal(s, empty));
+}
+
+#[test]
+fn string_set() {
+    let mut vm = VM::new();
+    let s = vm.string("aλc".to_string());
+    let copy = vm.string_copy(s).unwrap();
+    assert_ne!(s, copy);
+
+    assert_eq!(Ok(()), vm.string_set(s, 0, '€'));
+    assert_eq!(Ok(()), vm.string_set(s, 1, 'b'));
+    assert_eq!(Ok(()), vm.string_set(s, 2, '𝄞'));
+    assert_eq!(Some("€b𝄞"), vm.as_str(s));
+    assert_eq!(Some("aλc"), vm.as_str(copy));
+
+    let err = vm.string_set(s, 3, 'x').unwrap_err();
+    assert_eq!(GinkgoError::IndexOutOfBounds { index: 3, len: 3, kind: "string" }, err);
+    assert_eq!("index 3 out of range for string of length 3", err.to_string());
+    assert_eq!(Err(GinkgoError::TypeMismatch("string")), vm.string_set(vm.int(1), 0, 'x'));
+    assert_eq!(None, vm.string_copy(vm.int(1)));
+
+    vm.freeze(s);
+    assert_eq!(Err(GinkgoError::Immutable), vm.string_set(s, 0, 'x'));
+    assert_eq!(Ok(()), vm.string_set(copy, 0, 'x'));
+}
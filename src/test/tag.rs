@@ -0,0 +1,37 @@
+use crate::*;
+
+#[test]
+fn tags() {
+    let mut vm = VM::new();
+    let a = vm.cons(vm.int(1), vm.int(2));
+    let b = vm.cons(vm.int(1), vm.int(2));
+    assert_eq!(None, vm.get_tag(a));
+    vm.set_tag(a, 7);
+    assert_eq!(Some(7), vm.get_tag(a));
+    assert_eq!(None, vm.get_tag(b));
+    vm.set_tag(a, 8);
+    assert_eq!(Some(8), vm.get_tag(a));
+    assert_eq!(Some(8), vm.remove_tag(a));
+    assert_eq!(None, vm.get_tag(a));
+    assert_eq!(None, vm.remove_tag(a));
+
+    vm.set_tag(vm.int(1), 3);
+    assert_eq!(None, vm.get_tag(vm.int(1)));
+    assert_eq!(None, vm.get_tag(Object::Nil));
+}
+
+#[test]
+fn gc() {
+    let mut vm = VM::new();
+    let kept = vm.cons(Object::Nil, Object::Nil).root(&mut vm);
+    let lost = vm.cons(Object::Nil, Object::Nil);
+    vm.set_tag(kept.unroot(), 1);
+    vm.set_tag(lost, 2);
+    vm.gc();
+    assert_eq!(Some(1), vm.get_tag(kept.unroot()));
+    assert_eq!(1, vm.tags.len());
+
+    let sendable = vm.into_sendable(&[kept.unroot()]).ok().unwrap();
+    let (vm, roots) = sendable.into_vm();
+    assert_eq!(Some(1), vm.get_tag(roots[0].unroot()));
+}
@@ -75,6 +75,9 @@ fn floats() {
     assert_eq!("0.0", format!("{}", vm.wrap(vm.float(0.0))));
     assert_eq!("0.1", format!("{}", vm.wrap(vm.float(0.1))));
     assert_eq!("123.45", format!("{}", vm.wrap(vm.float(123.45))));
+    assert_eq!("+inf.0", format!("{}", vm.wrap(vm.float(f64::INFINITY))));
+    assert_eq!("-inf.0", format!("{}", vm.wrap(vm.float(f64::NEG_INFINITY))));
+    assert_eq!("+nan.0", format!("{}", vm.wrap(vm.float(f64::NAN))));
 
     assert_eq!(vm.float(1.0), vm.float(1.0));
     assert_eq!(vm.float(-1.0), vm.float(-1.0));
@@ -140,3 +143,179 @@ fn vectors() {
     assert_eq!(Some(Object::Nil), vm.vec_get(v, 1));
     assert_eq!(Some(vm.float(2.3)), vm.vec_get(v, 2));
 }
+
+#[test]
+fn vectors_from_iter() {
+    let mut vm = VM::new();
+
+    let v = vm.vec_from_iter((0..3).map(|i| Object::S(SVal::Int(i))));
+    assert_eq!("#(0 1 2)", format!("{}", vm.wrap(v)));
+    assert_eq!(Some(vm.int(2)), vm.vec_get(v, 2));
+    assert_eq!(None, vm.vec_get(v, 3));
+
+    let v = vm.vec_from_iter(vec![Object::Nil, Object::True]);
+    assert_eq!("#(nil #t)", format!("{}", vm.wrap(v)));
+
+    let v = vm.vec_from_iter(std::iter::empty());
+    assert_eq!("#()", format!("{}", vm.wrap(v)));
+    assert_eq!(None, vm.vec_get(v, 0));
+}
+
+#[test]
+fn vectors_round_trip() {
+    let mut vm = VM::new();
+    let empty = vm.vec(0);
+    let s = vm.string("a\nb\t\"c\"\\".to_string());
+    let single = vm.vec_from_iter(vec![s]);
+    let nested = vm.vec_from_iter(vec![empty, single, s]);
+
+    for (v, printed) in [
+        (empty, "#()"),
+        (single, r#"#("a\nb\t\"c\"\\")"#),
+        (nested, r#"#(#() #("a\nb\t\"c\"\\") "a\nb\t\"c\"\\")"#),
+    ] {
+        assert_eq!(printed, format!("{}", vm.wrap(v)));
+        let read = vm.read(printed).unwrap();
+        assert!(vm.equal(v, read), "{}", printed);
+    }
+}
+
+#[test]
+fn debug_string() {
+    let mut vm = VM::new();
+    assert_eq!("#<int 1>", vm.debug_string(vm.int(1)));
+    assert_eq!("#<nil nil>", vm.debug_string(Object::Nil));
+    assert_eq!("#<char #\\a>", vm.debug_string(vm.char('a')));
+
+    let l = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+    let m = vm.list(&[vm.int(1), vm.int(2), vm.int(3)]);
+    let debug = vm.debug_string(l);
+    assert!(debug.starts_with("#<cons @0x"), "{}", debug);
+    assert!(debug.ends_with(" (1 2 3)>"), "{}", debug);
+    assert_ne!(debug, vm.debug_string(m));
+    assert_eq!(debug, vm.debug_string(l));
+
+    let s = vm.string("x".to_string());
+    assert!(vm.debug_string(s).starts_with("#<string @"));
+    vm.gc();
+    assert!(vm.debug_string(l).starts_with("#<dead @"));
+}
+
+#[test]
+fn undefined_display() {
+    let mut vm = VM::new();
+    let l = vm.list(&[vm.int(1), Object::Undef]);
+    vm.set_undefined_display("; no value");
+    assert_eq!("; no value", format!("{}", vm.wrap(Object::Undef)));
+    assert_eq!("(1 ; no value)", format!("{}", vm.wrap(l)));
+
+    vm.set_undefined_display("");
+    assert_eq!("", format!("{}", vm.wrap(Object::Undef)));
+    let (copy, _) = vm.snapshot(&[]).unwrap();
+    assert_eq!("", format!("{}", copy.wrap(Object::Undef)));
+
+    vm.set_undefined_display("#undefined");
+    assert_eq!("(1 #undefined)", format!("{}", vm.wrap(l)));
+}
+
+#[test]
+fn hide_undefined() {
+    let mut vm = VM::new();
+    let v = vm.vec(3);
+    vm.vec_set(v, 1, vm.int(2)).unwrap();
+    let l = vm.cons(Object::Undef, Object::Undef);
+    vm.set_hide_undefined(true);
+    assert_eq!("#(_ 2 _)", format!("{}", vm.wrap(v)));
+    assert_eq!("(_ . _)", format!("{}", vm.wrap(l)));
+    assert_eq!("#undefined", format!("{}", vm.wrap(Object::Undef)));
+
+    vm.set_hide_undefined(false);
+    assert_eq!("#(#undefined 2 #undefined)", format!("{}", vm.wrap(v)));
+}
+
+#[test]
+fn print_radix() {
+    let mut vm = VM::new();
+    let l = vm.read("(255 -8 0 3/4 1.5)").unwrap();
+    assert_eq!(Some(()), vm.set_print_radix(16));
+    assert_eq!("(#xff #x-8 #x0 3/4 1.5)", format!("{}", vm.wrap(l)));
+    let mut out = String::new();
+    vm.write_truncated(&mut out, l, 10).unwrap();
+    assert_eq!("(#xff #x-8 #x0 3/4 1.5)", out);
+    let read = vm.read(&format!("{}", vm.wrap(l))).unwrap();
+    assert!(vm.equal(l, read));
+
+    vm.set_print_radix(2).unwrap();
+    assert_eq!("#b101", format!("{}", vm.wrap(vm.int(5))));
+    vm.set_print_radix(8).unwrap();
+    assert_eq!("#o-17", format!("{}", vm.wrap(vm.int(-15))));
+    let (copy, _) = vm.snapshot(&[]).unwrap();
+    assert_eq!("#o10", format!("{}", copy.wrap(copy.int(8))));
+
+    for radix in [0, 1, 3, 36, 37] {
+        assert_eq!(None, vm.set_print_radix(radix), "{}", radix);
+    }
+    assert_eq!("#o10", format!("{}", vm.wrap(vm.int(8))));
+    vm.set_print_radix(10).unwrap();
+    assert_eq!("255", format!("{}", vm.wrap(vm.int(255))));
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn print_radix_bignum() {
+    let mut vm = VM::new();
+    let big = vm.add(vm.int(isize::MAX), vm.int(1)).unwrap();
+    vm.set_print_radix(16).unwrap();
+    assert_eq!("#x8000000000000000", format!("{}", vm.wrap(big)));
+    let read = vm.read("#x8000000000000000").unwrap();
+    assert!(vm.equal(big, read));
+}
+
+#[test]
+fn deep_nesting() {
+    let mut vm = VM::new();
+    let mut obj = vm.int(0);
+    let (mut opens, mut closes) = (Vec::new(), String::new());
+    for i in 0..100_000 {
+        let (open, close) = match i % 3 {
+            0 => {
+                obj = vm.list(&[obj]);
+                ("(", ")")
+            }
+            1 => {
+                obj = vm.cons(obj, vm.int(1));
+                ("(", " . 1)")
+            }
+            _ => {
+                obj = vm.vec_from_iter(vec![obj]);
+                ("#(", ")")
+            }
+        };
+        opens.push(open);
+        closes.push_str(close);
+    }
+    opens.reverse();
+    assert_eq!(format!("{}0{}", opens.concat(), closes), format!("{}", vm.wrap(obj)));
+}
+
+#[test]
+fn write_truncated() {
+    let mut vm = VM::new();
+    let big = vm.string("a".repeat(1_000_000));
+    let small = vm.string("λ\n".to_string());
+    let l = vm.list(&[small, big, vm.int(1)]);
+
+    let mut out = String::new();
+    vm.write_truncated(&mut out, big, 4).unwrap();
+    assert_eq!("\"aaaa...<999996 more chars>\"", out);
+
+    let mut out = String::new();
+    vm.write_truncated(&mut out, l, 2).unwrap();
+    assert_eq!("(\"λ\\n\" \"aa...<999998 more chars>\" 1)", out);
+
+    let mut out = String::new();
+    vm.write_truncated(&mut out, l, 0).unwrap();
+    assert_eq!("(\"...<2 more chars>\" \"...<1000000 more chars>\" 1)", out);
+
+    assert_eq!(1_000_002, format!("{}", vm.wrap(big)).len());
+}
@@ -0,0 +1,54 @@
+use crate::*;
+
+#[test]
+fn truthiness() {
+    let mut vm = VM::new();
+    assert!(!vm.is_truthy(Object::False));
+    assert!(vm.is_truthy(Object::True));
+    assert!(vm.is_truthy(Object::Nil));
+    assert!(vm.is_truthy(Object::Undef));
+    assert!(vm.is_truthy(vm.int(0)));
+    assert!(vm.is_truthy(vm.float(0.0)));
+    let s = vm.string(String::new());
+    assert!(vm.is_truthy(s));
+    let v = vm.vec(0);
+    assert!(vm.is_truthy(v));
+
+    assert_eq!(Object::True, vm.not(Object::False));
+    assert_eq!(Object::False, vm.not(Object::True));
+    assert_eq!(Object::False, vm.not(Object::Nil));
+    assert_eq!(Object::False, vm.not(vm.int(0)));
+    assert_eq!(Object::False, vm.not(s));
+}
+
+#[test]
+fn boolean() {
+    let vm = VM::new();
+    assert_eq!(Object::True, vm.boolean(true));
+    assert_eq!(Object::False, vm.boolean(false));
+    assert_eq!(Some(true), vm.boolean(true).as_bool());
+    assert!(!vm.is_truthy(vm.boolean(false)));
+    assert_eq!(0, vm.heapsize());
+}
+
+#[test]
+fn nil_is_false() {
+    let mut vm = VM::new();
+    vm.set_nil_is_false(true);
+    assert!(!vm.is_truthy(Object::Nil));
+    assert!(!vm.is_truthy(Object::False));
+    assert!(vm.is_truthy(Object::Undef));
+    assert!(vm.is_truthy(vm.int(0)));
+    assert_eq!(Object::True, vm.not(Object::Nil));
+
+    let env = vm.make_env(None);
+    let expr = vm.read("(if (quote ()) 1 2)").unwrap();
+    assert_eq!(Ok(vm.int(2)), vm.eval(expr, env));
+
+    let (copy, _) = vm.snapshot(&[]).unwrap();
+    assert!(!copy.is_truthy(Object::Nil));
+
+    vm.set_nil_is_false(false);
+    assert!(vm.is_truthy(Object::Nil));
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+}
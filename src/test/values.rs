@@ -0,0 +1,71 @@
+use crate::*;
+use super::natives::sum;
+
+fn two(vm: &mut VM, _: &[Object]) -> Object {
+    vm.values(&[vm.int(1), vm.int(2)])
+}
+
+fn none(vm: &mut VM, _: &[Object]) -> Object {
+    vm.values(&[])
+}
+
+#[test]
+fn values() {
+    let mut vm = VM::new();
+    assert_eq!(vm.int(1), vm.values(&[vm.int(1)]));
+    assert_eq!(0, vm.heapsize());
+
+    let s = vm.string("x".to_string());
+    let v = vm.values(&[vm.int(1), s]).root(&mut vm);
+    assert_eq!(vec![vm.int(1), s], vm.values_to_vec(v.unroot()));
+    assert_eq!(vm.int(1), vm.first_value(v.unroot()));
+    assert_eq!("#<values 1 \"x\">", format!("{}", vm.wrap(v.unroot())));
+
+    // The values keep their contents alive
+    vm.gc();
+    assert_eq!(2, vm.heapsize());
+    assert_eq!(Some("x"), vm.as_str(s));
+
+    let empty = vm.values(&[]);
+    assert_eq!(Vec::<Object>::new(), vm.values_to_vec(empty));
+    assert_eq!(Object::Undef, vm.first_value(empty));
+    assert_eq!("#<values>", format!("{}", vm.wrap(empty)));
+
+    assert_eq!(vec![vm.int(3)], vm.values_to_vec(vm.int(3)));
+    assert_eq!(vm.int(3), vm.first_value(vm.int(3)));
+}
+
+#[test]
+fn call_with_values() {
+    let mut vm = VM::new();
+    let producer = vm.native(two);
+    let consumer = vm.native(sum);
+    assert_eq!(Some(vm.int(3)), vm.call_with_values(producer, consumer));
+    let producer = vm.native(none);
+    assert_eq!(Some(vm.int(0)), vm.call_with_values(producer, consumer));
+    assert_eq!(None, vm.call_with_values(vm.int(1), consumer));
+}
+
+#[test]
+fn single_value_contexts() {
+    let mut vm = VM::new();
+    let env = vm.make_env(None);
+    let names = ["two", "none", "+"];
+    let procs = [vm.native(two), vm.native(none), vm.native(sum)];
+    for (name, proc) in names.iter().zip(procs) {
+        let sym = vm.symbol(name);
+        vm.env_define(env, sym, proc).unwrap();
+    }
+
+    let expr = vm.read("(+ (two) (two) 10)").unwrap();
+    assert_eq!(Ok(vm.int(12)), vm.eval(expr, env));
+    let expr = vm.read("(begin (define x (two)) x)").unwrap();
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+    let expr = vm.read("(if (none) 1 2)").unwrap();
+    assert_eq!(Ok(vm.int(1)), vm.eval(expr, env));
+
+    // Values in tail position are returned as is
+    let expr = vm.read("((lambda () (two)))").unwrap();
+    let result = vm.eval(expr, env).unwrap();
+    assert_eq!(vec![vm.int(1), vm.int(2)], vm.values_to_vec(result));
+}
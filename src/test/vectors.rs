@@ -0,0 +1,210 @@
+use crate::*;
+
+#[test]
+fn sort() {
+    let mut vm = VM::new();
+    let v = vm.vec(4);
+    vm.vec_set(v, 0, vm.int(3)).unwrap();
+    vm.vec_set(v, 1, vm.int(-1)).unwrap();
+    vm.vec_set(v, 2, vm.float(2.5)).unwrap();
+    vm.vec_set(v, 3, vm.int(0)).unwrap();
+    vm.vec_sort(v, |vm, a, b| vm.num_cmp(a, b).unwrap()).unwrap();
+    assert_eq!("#(-1 0 2.5 3)", format!("{}", vm.wrap(v)));
+
+    let v = vm.vec(3);
+    let strings: Vec<_> = ["b", "c", "a"].iter().map(|s| vm.string(s.to_string())).collect();
+    for (i, s) in strings.iter().enumerate() {
+        vm.vec_set(v, i, *s).unwrap();
+    }
+    vm.vec_sort(v, |vm, a, b| {
+        let a = format!("{}", vm.wrap(a));
+        let b = format!("{}", vm.wrap(b));
+        a.cmp(&b)
+    }).unwrap();
+    assert_eq!("#(\"a\" \"b\" \"c\")", format!("{}", vm.wrap(v)));
+
+    let v = vm.vec(0);
+    assert_eq!(Ok(()), vm.vec_sort(v, |_, _, _| std::cmp::Ordering::Equal));
+    let l = vm.list(&[vm.int(2), vm.int(1)]);
+    assert_eq!(Err(GinkgoError::TypeMismatch("vector")), vm.vec_sort(l, |_, _, _| std::cmp::Ordering::Equal));
+}
+
+#[test]
+fn search() {
+    let mut vm = VM::new();
+    let v = vm.vec(4);
+    let s = vm.string("key".to_string());
+    vm.vec_set(v, 0, vm.int(1)).unwrap();
+    vm.vec_set(v, 1, s).unwrap();
+    vm.vec_set(v, 2, vm.int(2)).unwrap();
+    vm.vec_set(v, 3, vm.int(1)).unwrap();
+
+    assert_eq!(Some(0), vm.vec_index_of(v, vm.int(1)));
+    assert_eq!(Some(2), vm.vec_index_of(v, vm.int(2)));
+    let needle = vm.string("key".to_string());
+    assert_eq!(Some(1), vm.vec_index_of(v, needle));
+    assert_eq!(None, vm.vec_index_of(v, vm.float(1.0)));
+    assert!(vm.vec_contains(v, needle));
+    assert!(!vm.vec_contains(v, Object::Nil));
+
+    let l = vm.list(&[vm.int(1)]);
+    assert_eq!(None, vm.vec_index_of(l, vm.int(1)));
+    assert!(!vm.vec_contains(l, vm.int(1)));
+}
+
+#[test]
+fn map() {
+    let mut vm = VM::new();
+    let v = vm.vec(3);
+    for i in 0..3 {
+        vm.vec_set(v, i, vm.int(i as isize)).unwrap();
+    }
+    let w = vm.vec_map(v, |vm, x| vm.mul(x, vm.int(10)).unwrap()).unwrap();
+    assert_eq!("#(0 10 20)", format!("{}", vm.wrap(w)));
+    assert_eq!("#(0 1 2)", format!("{}", vm.wrap(v)));
+    assert_ne!(v, w);
+
+    let w = vm.vec_map(v, |vm, x| vm.cons(x, Object::Nil)).unwrap();
+    assert_eq!("#((0) (1) (2))", format!("{}", vm.wrap(w)));
+
+    let e = vm.vec(0);
+    let w = vm.vec_map(e, |_, x| x).unwrap();
+    assert_eq!("#()", format!("{}", vm.wrap(w)));
+    assert_eq!(None, vm.vec_map(Object::Nil, |_, x| x));
+}
+
+#[test]
+fn vec_ref() {
+    let mut vm = VM::new();
+    let v = vm.read("#(1 2 3)").unwrap();
+    assert_eq!(Ok(vm.int(3)), vm.vec_ref(v, 2));
+    let err = vm.vec_ref(v, 5).unwrap_err();
+    assert_eq!(GinkgoError::IndexOutOfBounds { index: 5, len: 3, kind: "vector" }, err);
+    assert_eq!("index 5 out of range for vector of length 3", err.to_string());
+    assert_eq!(None, vm.vec_get(v, 5));
+
+    let l = vm.list(&[vm.int(1)]);
+    assert_eq!(Err(GinkgoError::TypeMismatch("vector")), vm.vec_ref(l, 0));
+    assert_eq!(None, vm.vec_get(l, 0));
+    let e = vm.vec(0);
+    assert_eq!(Err(GinkgoError::IndexOutOfBounds { index: 0, len: 0, kind: "vector" }), vm.vec_ref(e, 0));
+}
+
+#[test]
+fn to_rust() {
+    let mut vm = VM::new();
+    let v = vm.vec(3);
+    vm.vec_set(v, 1, vm.int(1)).unwrap();
+    vm.vec_set(v, 2, Object::True).unwrap();
+    assert_eq!(Some(vec![Object::Undef, vm.int(1), Object::True]), vm.vec_to_rust(v));
+
+    let e = vm.vec(0);
+    assert_eq!(Some(vec![]), vm.vec_to_rust(e));
+    assert_eq!(None, vm.vec_to_rust(vm.int(1)));
+}
+
+#[test]
+fn iter() {
+    let mut vm = VM::new();
+    let items: Vec<_> = (0..5).map(|i| vm.int(i)).collect();
+    let v = vm.vec_from_iter(items);
+    assert_eq!(2, vm.vec_iter(v).unwrap().filter(|item| item.as_int().unwrap() % 2 == 1).count());
+    assert_eq!(vm.vec_to_rust(v).unwrap(), vm.vec_iter(v).unwrap().collect::<Vec<_>>());
+
+    let e = vm.vec(0);
+    assert_eq!(0, vm.vec_iter(e).unwrap().count());
+    assert!(vm.vec_iter(vm.int(1)).is_none());
+}
+
+#[test]
+fn equality() {
+    let mut vm = VM::new();
+    let inner_a = vm.list(&[vm.int(1), vm.int(2)]);
+    let inner_b = vm.list(&[vm.int(1), vm.int(2)]);
+    let a = vm.vec_from_iter(vec![vm.int(0), inner_a]);
+    let b = vm.vec_from_iter(vec![vm.int(0), inner_b]);
+    assert_ne!(a, b);
+    assert!(!vm.eqv(a, b));
+    assert!(vm.equal(a, b));
+    assert_eq!(Some(true), vm.vec_equal(a, b));
+    assert!(vm.eqv(a, a));
+
+    let c = vm.vec_from_iter(vec![vm.int(0), vm.int(1)]);
+    let d = vm.vec_from_iter(vec![vm.int(0)]);
+    assert_eq!(Some(false), vm.vec_equal(a, c));
+    assert_eq!(Some(false), vm.vec_equal(a, d));
+    let (e, f) = (vm.vec(0), vm.vec(0));
+    assert_eq!(Some(true), vm.vec_equal(e, f));
+    assert_eq!(None, vm.vec_equal(a, inner_a));
+    assert_eq!(None, vm.vec_equal(vm.int(1), a));
+}
+
+#[test]
+fn vec_cow() {
+    fn shares(vm: &VM, a: Object, b: Object) -> bool {
+        match (vm.direct(a), vm.direct(b)) {
+            (DObj::H(HVal::Vec(a)), DObj::H(HVal::Vec(b))) => std::rc::Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    let mut vm = VM::new();
+    let s = vm.string("s".to_string());
+    let a = vm.vec_from_iter(vec![vm.int(1), s]);
+    let b = vm.vec_cow_clone(a).unwrap();
+    let c = vm.vec_cow_clone(a).unwrap();
+    assert_ne!(a, b);
+    assert!(shares(&vm, a, b));
+    assert!(vm.equal(a, b));
+    assert_eq!(None, vm.vec_cow_clone(s));
+
+    vm.vec_set(b, 0, vm.int(2)).unwrap();
+    assert!(!shares(&vm, a, b));
+    assert!(shares(&vm, a, c));
+    assert_eq!(Some(vm.int(1)), vm.vec_get(a, 0));
+    assert_eq!(Some(vm.int(2)), vm.vec_get(b, 0));
+
+    vm.vec_push(a, vm.int(3)).unwrap();
+    assert!(!shares(&vm, a, c));
+    assert_eq!("#(1 \"s\" 3)", format!("{}", vm.wrap(a)));
+    assert_eq!("#(1 \"s\")", format!("{}", vm.wrap(c)));
+    assert_eq!(Err(GinkgoError::TypeMismatch("vector")), vm.vec_push(s, s));
+
+    // Shared elements survive as long as any vector holding them
+    let d = vm.vec_cow_clone(c).unwrap();
+    let d = d.root(&mut vm);
+    vm.gc();
+    assert_eq!(2, vm.heapsize());
+    assert_eq!("#(1 \"s\")", format!("{}", vm.wrap(d.unroot())));
+}
+
+#[test]
+fn reverse() {
+    let mut vm = VM::new();
+    let v = vm.read("#(1 2 3 4)").unwrap();
+    let r = vm.vec_reversed(v).unwrap();
+    assert_eq!("#(4 3 2 1)", format!("{}", vm.wrap(r)));
+    assert_eq!("#(1 2 3 4)", format!("{}", vm.wrap(v)));
+    vm.vec_reverse(v).unwrap();
+    assert_eq!("#(4 3 2 1)", format!("{}", vm.wrap(v)));
+
+    let c = vm.vec_cow_clone(v).unwrap();
+    vm.vec_reverse(c).unwrap();
+    assert_eq!("#(1 2 3 4)", format!("{}", vm.wrap(c)));
+    assert_eq!("#(4 3 2 1)", format!("{}", vm.wrap(v)));
+
+    for src in ["#()", "#(1)"] {
+        let v = vm.read(src).unwrap();
+        vm.vec_reverse(v).unwrap();
+        assert_eq!(src, format!("{}", vm.wrap(v)));
+        let r = vm.vec_reversed(v).unwrap();
+        assert_eq!(src, format!("{}", vm.wrap(r)));
+        assert_ne!(v, r);
+    }
+
+    let l = vm.list(&[vm.int(1)]);
+    assert_eq!(Err(GinkgoError::TypeMismatch("vector")), vm.vec_reverse(l));
+    assert_eq!(None, vm.vec_reversed(l));
+    vm.freeze(v);
+    assert_eq!(Err(GinkgoError::Immutable), vm.vec_reverse(v));
+}
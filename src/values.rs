@@ -0,0 +1,45 @@
+use crate::{DObj, GObj, HVal, Object, VM};
+
+
+impl VM {
+    /// Return multiple values, like Scheme's `values`.  A single value
+    /// is returned as is.  Other counts are wrapped in a values object,
+    /// which single-value contexts such as procedure arguments reduce
+    /// to its first value, or undefined if there are none.
+    pub fn values(&mut self, objs: &[Object]) -> Object {
+        match objs {
+            [obj] => *obj,
+            _ => self.alloc(HVal::Values(objs.to_vec())),
+        }
+    }
+
+    /// Collect the values represented by an object.  A values object
+    /// gives its contents, and any other object gives itself.
+    pub fn values_to_vec(&self, obj: impl GObj) -> Vec<Object> {
+        let obj = obj.unroot();
+        match self.direct(obj) {
+            DObj::H(HVal::Values(values)) => values.clone(),
+            _ => vec![obj],
+        }
+    }
+
+    /// Get the first value represented by an object, as used in
+    /// single-value contexts.  This is undefined for zero values.
+    pub fn first_value(&self, obj: impl GObj) -> Object {
+        let obj = obj.unroot();
+        match self.direct(obj) {
+            DObj::H(HVal::Values(values)) => values.first().copied().unwrap_or(Object::Undef),
+            _ => obj,
+        }
+    }
+
+    /// Call a producer procedure with no arguments, and pass the values
+    /// it returns as arguments to a consumer procedure, like Scheme's
+    /// `call-with-values`.  Returns `None` if either call fails.
+    pub fn call_with_values(&mut self, producer: impl GObj, consumer: impl GObj) -> Option<Object> {
+        let consumer = consumer.unroot().root(self);
+        let values = self.apply(producer, &[])?;
+        let args = self.values_to_vec(values);
+        self.apply(consumer, &args)
+    }
+}
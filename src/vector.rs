@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::{DObj, GinkgoError, GObj, HVal, Object, VM};
+
+
+impl VM {
+    /// Copy the elements of a vector into a Rust vector.  Returns
+    /// `None` if the object is not a vector.
+    pub fn vec_to_rust(&self, obj: impl GObj) -> Option<Vec<Object>> {
+        match self.direct(obj) {
+            DObj::H(HVal::Vec(vec)) => Some(vec.to_vec()),
+            _ => None,
+        }
+    }
+
+    /// Iterate over the elements of a vector without copying them.
+    /// Returns `None` if the object is not a vector.  The iterator
+    /// borrows the VM, so it cannot be mutated during iteration.
+    pub fn vec_iter(&self, obj: impl GObj) -> Option<impl Iterator<Item = Object> + '_> {
+        match self.direct(obj) {
+            DObj::H(HVal::Vec(vec)) => Some(vec.iter().copied()),
+            _ => None,
+        }
+    }
+
+    /// Find the index of the first element of a vector that is `equal`
+    /// to the needle.  Returns `None` if there is no such element, or
+    /// if the object is not a vector.
+    pub fn vec_index_of(&self, obj: impl GObj, needle: impl GObj) -> Option<usize> {
+        let needle = needle.unroot();
+        match self.direct(obj) {
+            DObj::H(HVal::Vec(vec)) => vec.iter().position(|item| self.equal(*item, needle)),
+            _ => None,
+        }
+    }
+
+    /// Check whether a vector has an element that is `equal` to the
+    /// needle.
+    pub fn vec_contains(&self, obj: impl GObj, needle: impl GObj) -> bool {
+        self.vec_index_of(obj, needle).is_some()
+    }
+
+    /// Compare two vectors element by element with `equal`.  Returns
+    /// `None` if either object is not a vector.
+    pub fn vec_equal(&self, a: impl GObj, b: impl GObj) -> Option<bool> {
+        let (a, b) = (a.unroot(), b.unroot());
+        match (self.direct(a), self.direct(b)) {
+            (DObj::H(HVal::Vec(_)), DObj::H(HVal::Vec(_))) => Some(self.equal(a, b)),
+            _ => None,
+        }
+    }
+
+    /// Apply a function to each element of a vector, and return a new
+    /// vector of the results.  Returns `None` if the object is not a
+    /// vector.
+    pub fn vec_map(
+        &mut self,
+        obj: impl GObj,
+        mut f: impl FnMut(&mut VM, Object) -> Object,
+    ) -> Option<Object> {
+        // The function may collect garbage, so the elements are kept
+        // alive through the vector, and the results are rooted
+        let obj = obj.unroot();
+        let _rooted = obj.root(self);
+        let items = self.vec_to_rust(obj)?;
+        let results: Vec<_> = items.into_iter().map(|item| f(self, item).root(self)).collect();
+        Some(self.vec_from_iter(results.iter().map(GObj::unroot)))
+    }
+
+    /// Return a new vector sharing the elements of another.  The
+    /// elements are copied when either vector is first mutated, so
+    /// this is cheap when the copy is mostly read.  Returns `None` if
+    /// the object is not a vector.
+    pub fn vec_cow_clone(&mut self, obj: impl GObj) -> Option<Object> {
+        let items = match self.direct(obj) {
+            DObj::H(HVal::Vec(vec)) => vec.clone(),
+            _ => return None,
+        };
+        Some(self.alloc(HVal::Vec(items)))
+    }
+
+    /// Append an element to a vector.  If the vector shares its
+    /// elements with others, it gets its own copy first.  Fails with
+    /// `GinkgoError::OutOfMemory`, leaving the vector unchanged, if
+    /// growing it exceeds the heap limit.
+    pub fn vec_push(&mut self, obj: impl GObj, val: impl GObj) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
+        let capacity = match self.direct_mut(obj) {
+            DObj::H(HVal::Vec(vec)) => {
+                let vec = Rc::make_mut(vec);
+                let capacity = vec.capacity();
+                vec.push(val.unroot());
+                capacity
+            }
+            _ => return Err(GinkgoError::TypeMismatch("vector")),
+        };
+        self.grown(obj, |val| if let HVal::Vec(vec) = val {
+            let vec = Rc::make_mut(vec);
+            vec.pop();
+            vec.shrink_to(capacity);
+        })
+    }
+
+    /// Reverse the elements of a vector in place.  If the vector shares
+    /// its elements with others, it gets its own copy first.
+    pub fn vec_reverse(&mut self, obj: impl GObj) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
+        match self.direct_mut(obj) {
+            DObj::H(HVal::Vec(vec)) => {
+                Rc::make_mut(vec).reverse();
+                Ok(())
+            }
+            _ => Err(GinkgoError::TypeMismatch("vector")),
+        }
+    }
+
+    /// Return a new vector with the elements of another in reverse
+    /// order.  Returns `None` if the object is not a vector.
+    pub fn vec_reversed(&mut self, obj: impl GObj) -> Option<Object> {
+        let mut items = self.vec_to_rust(obj)?;
+        items.reverse();
+        Some(self.vec_from_iter(items))
+    }
+
+    /// Sort the elements of a vector in place according to a
+    /// comparator.  The sort is stable.
+    pub fn vec_sort(
+        &mut self,
+        obj: impl GObj,
+        mut cmp: impl FnMut(&mut VM, Object, Object) -> Ordering,
+    ) -> Result<(), GinkgoError> {
+        let obj = obj.unroot();
+        self.check_mutable(obj)?;
+        let _rooted = obj.root(self);
+
+        // The comparator needs the VM, so sort a copy and write it back
+        let mut items = self.vec_to_rust(obj).ok_or(GinkgoError::TypeMismatch("vector"))?;
+        items.sort_by(|a, b| cmp(self, *a, *b));
+
+        match self.direct_mut(obj) {
+            DObj::H(HVal::Vec(vec)) => {
+                *vec = Rc::new(items);
+                Ok(())
+            }
+            _ => Err(GinkgoError::TypeMismatch("vector")),
+        }
+    }
+}
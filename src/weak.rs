@@ -0,0 +1,42 @@
+use crate::{GObj, Object, SVal, VM};
+
+
+/// Reference to an object that does not keep it alive.  Obtained from
+/// `VM::weak` and resolved with `VM::upgrade`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeakObject(Target);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Target {
+    S(SVal),
+    H(usize),
+}
+
+
+impl VM {
+    /// Create a weak reference to an object.
+    pub fn weak(&mut self, obj: impl GObj) -> WeakObject {
+        match obj.unroot() {
+            Object::S(v) => WeakObject(Target::S(v)),
+            Object::H(handle) => {
+                let id = self.next_weak;
+                self.next_weak += 1;
+                self.weaks.insert(id, handle);
+                WeakObject(Target::H(id))
+            }
+        }
+    }
+
+    /// Get the object a weak reference refers to.  Returns `None` once
+    /// the object has been freed, that is, after the first garbage
+    /// collection (explicit or automatic) in which it was not
+    /// reachable from a root.  Until then the object is returned even
+    /// if it is garbage.  Weak references to stack values always
+    /// succeed.
+    pub fn upgrade(&self, weak: &WeakObject) -> Option<Object> {
+        match weak.0 {
+            Target::S(v) => Some(Object::S(v)),
+            Target::H(id) => self.weaks.get(&id).map(|&handle| Object::H(handle)),
+        }
+    }
+}